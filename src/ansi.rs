@@ -0,0 +1,107 @@
+use std::borrow::Cow;
+
+use memchr::memchr;
+
+/// Remove the ANSI color codes (SGR escape sequences like `\x1b[32m`) that tools like
+/// `git diff --color=always` add to their output, so that it can be parsed.
+///
+/// Only allocates if there are color codes to remove. Any other escape sequences are kept.
+///
+/// # Example
+///
+/// ```
+/// # use patch::Patch;
+/// let colored = "\
+/// \x1b[1m--- a/hello.txt\x1b[m
+/// \x1b[1m+++ b/hello.txt\x1b[m
+/// \x1b[36m@@ -1 +1 @@\x1b[m
+/// \x1b[31m-hello\x1b[m
+/// \x1b[32m+goodbye\x1b[m\n";
+///
+/// let stripped = patch::strip_ansi_colors(colored);
+/// let patch = Patch::from_single(&stripped).unwrap();
+/// assert_eq!(&patch.new.path, "b/hello.txt");
+/// ```
+pub fn strip_ansi_colors(input: &str) -> Cow<'_, str> {
+    if !input.contains("\x1b[") {
+        return Cow::Borrowed(input);
+    }
+
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find("\x1b[") {
+        output.push_str(&rest[..start]);
+        match color_code_len(&rest.as_bytes()[start..]) {
+            Some(len) => rest = &rest[start + len..],
+            None => {
+                output.push_str("\x1b[");
+                rest = &rest[start + 2..];
+            }
+        }
+    }
+    output.push_str(rest);
+    Cow::Owned(output)
+}
+
+// Like `strip_ansi_colors`, but moves the rest of `bytes` over the color codes instead of
+// allocating, and returns the length of what's left
+pub(crate) fn strip_ansi_colors_in_place(bytes: &mut [u8]) -> usize {
+    let mut read = 0;
+    let mut write = 0;
+    loop {
+        let end = memchr(b'\x1b', &bytes[read..]).map_or(bytes.len(), |i| read + i);
+        bytes.copy_within(read..end, write);
+        write += end - read;
+        if end == bytes.len() {
+            return write;
+        }
+        match color_code_len(&bytes[end..]) {
+            Some(len) => read = end + len,
+            None => {
+                bytes[write] = b'\x1b';
+                write += 1;
+                read = end + 1;
+            }
+        }
+    }
+}
+
+// The length of the color code at the start of `bytes`, e.g. 7 for `\x1b[32m`
+fn color_code_len(bytes: &[u8]) -> Option<usize> {
+    let params = bytes.strip_prefix(b"\x1b[")?;
+    let len = params
+        .iter()
+        .position(|byte| !matches!(byte, b'0'..=b'9' | b';' | b':'))?;
+    (params[len] == b'm').then_some(len + 3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_strip_ansi_colors() {
+        assert!(matches!(strip_ansi_colors("plain"), Cow::Borrowed("plain")));
+        assert_eq!(strip_ansi_colors("\x1b[32m+added\x1b[m"), "+added");
+        assert_eq!(strip_ansi_colors("\x1b[1;38;5;208mx\x1b[0m y"), "x y");
+        // trailing whitespace highlighted by git
+        assert_eq!(strip_ansi_colors("+x\x1b[41m \x1b[m"), "+x ");
+        // not color codes
+        assert_eq!(strip_ansi_colors("\x1b[2J\x1b["), "\x1b[2J\x1b[");
+    }
+
+    #[test]
+    fn test_strip_ansi_colors_in_place() {
+        for input in [
+            "plain",
+            "\x1b[32m+added\x1b[m",
+            "+x\x1b[41m \x1b[m",
+            "\x1b[2J\x1b[",
+        ] {
+            let mut bytes = input.as_bytes().to_vec();
+            let len = strip_ansi_colors_in_place(&mut bytes);
+            assert_eq!(&bytes[..len], strip_ansi_colors(input).as_bytes());
+        }
+    }
+}
@@ -1,9 +1,26 @@
 use std::borrow::Cow;
+use std::error::Error;
 use std::fmt;
 
 use chrono::{DateTime, FixedOffset};
 
-use crate::parser::{parse_multiple_patches, parse_single_patch, ParseError};
+use crate::base85;
+use crate::context::context_format;
+use crate::dep3::Dep3Headers;
+use crate::describe::describe;
+use crate::options::ParserOptions;
+use crate::parser::{
+    iter_multiple_patches, parse_combined_patches, parse_git_diff_output, parse_header_line,
+    parse_headerless_patch, parse_hunk, parse_hunk_fragment, parse_multiple_patches,
+    parse_multiple_patches_lossy, parse_multiple_patches_partial,
+    parse_multiple_patches_with_spans, parse_multiple_patches_with_warnings, parse_normal_patch,
+    parse_patch_headers, parse_recursive_diff, parse_single_patch, ParseError,
+};
+use crate::scan::PatchHeaders;
+use crate::scope::{scope_of, Scope};
+use crate::stats::FileStat;
+use crate::warning::Warning;
+use crate::whitespace::{whitespace_errors, WhitespaceError};
 
 /// A complete patch summarizing the differences between two files
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -12,32 +29,223 @@ pub struct Patch<'a> {
     pub old: File<'a>,
     /// The file information of the `+` side of the diff, line prefix: `+++`
     pub new: File<'a>,
+    /// The extended header information provided by `git diff`, if any
+    pub git: Option<GitMetadata<'a>>,
+    /// The `Index:` line and property changes written by `svn diff`, if any
+    pub svn: Option<SvnMetadata<'a>>,
+    /// The `Index:` line and revision information written by `cvs diff`, if any
+    pub cvs: Option<CvsMetadata<'a>>,
+    /// The `====` line that `p4 diff` writes instead of the `---` and `+++` lines, if any
+    pub perforce: Option<PerforceMetadata<'a>>,
     /// hunks of differences; each hunk shows one area where the files differ
+    ///
+    /// This is empty for git patches that only change metadata, like a rename without any
+    /// content changes. Those have no `---` and `+++` lines, so `old` and `new` are taken from the
+    /// `diff --git` line instead.
     pub hunks: Vec<Hunk<'a>>,
-    /// true if the last line of the file ends in a newline character
+    /// The change to a binary file, which has no hunks or `---` and `+++` lines
+    pub binary: Option<BinaryPatch>,
+    /// true if the last line of the old file ends in a newline character
     ///
-    /// This will only be false if at the end of the patch we encounter the text:
-    /// `\ No newline at end of file`
-    pub end_newline: bool,
+    /// This will only be false if the last removed or context line of the patch is followed by
+    /// the text: `\ No newline at end of file`
+    pub old_end_newline: bool,
+    /// true if the last line of the new file ends in a newline character
+    ///
+    /// This will only be false if the last added or context line of the patch is followed by
+    /// the text: `\ No newline at end of file`
+    pub new_end_newline: bool,
+    /// The characters that end each line of the patch, which `Display` reproduces
+    ///
+    /// Set this to `LineEnding::Lf` to normalize a patch written with CRLF line endings.
+    pub line_ending: LineEnding,
+    /// true if the patch started with a UTF-8 byte order mark, which some Windows editors add
+    /// when saving a file. The mark is skipped when parsing and written again by `Display`.
+    pub byte_order_mark: bool,
+    /// The lines before the patch that aren't part of it, like a commit message or a comment,
+    /// including their line endings. `Display` writes them again before the patch.
+    pub preamble: Option<&'a str>,
 }
 
 impl<'a> fmt::Display for Patch<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Display implementations typically hold up the invariant that there is no trailing
         // newline. This isn't enforced, but it allows them to work well with `println!`
+        if self.byte_order_mark {
+            write!(f, "\u{feff}")?;
+        }
+        if let Some(preamble) = self.preamble {
+            write!(f, "{}", preamble)?;
+        }
+        match self.line_ending {
+            LineEnding::Lf => self.fmt_lines(f),
+            LineEnding::CrLf => self.fmt_lines(&mut CrLfWriter(f)),
+        }
+    }
+}
 
-        write!(f, "--- {}", self.old)?;
-        write!(f, "\n+++ {}", self.new)?;
-        for hunk in &self.hunks {
-            write!(f, "\n{}", hunk)?;
+// Writes `\r\n` for every `\n`, which is only ever written at the end of a line
+struct CrLfWriter<'f, 'g>(&'f mut fmt::Formatter<'g>);
+
+impl<'f, 'g> fmt::Write for CrLfWriter<'f, 'g> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for (i, line) in s.split('\n').enumerate() {
+            if i > 0 {
+                self.0.write_str("\r\n")?;
+            }
+            self.0.write_str(line)?;
         }
-        if !self.end_newline {
-            write!(f, "\n\\ No newline at end of file")?;
+        Ok(())
+    }
+}
+
+impl<'a> Patch<'a> {
+    fn fmt_lines(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        if let Some(git) = &self.git {
+            write!(f, "{}", git)?;
+            // Patches that only change metadata (e.g. renames) have nothing else
+            if self.hunks.is_empty() && self.binary.is_none() {
+                return Ok(());
+            }
+            writeln!(f)?;
+        }
+        if let Some(svn) = &self.svn {
+            write!(f, "Index: {}\n{}\n", svn.path, "=".repeat(67))?;
+        }
+        if let Some(cvs) = &self.cvs {
+            writeln!(f, "{}", cvs)?;
+        }
+        match &self.binary {
+            Some(BinaryPatch::Changed) => {
+                write!(f, "Binary files ")?;
+                maybe_escape_quote(f, &self.old.path, self.old.escaped)?;
+                write!(f, " and ")?;
+                maybe_escape_quote(f, &self.new.path, self.new.escaped)?;
+                return write!(f, " differ");
+            }
+            Some(BinaryPatch::Git { forward, reverse }) => {
+                // Each hunk is terminated by an empty line
+                write!(f, "GIT binary patch\n{}\n", forward)?;
+                if let Some(reverse) = reverse {
+                    write!(f, "\n{}\n", reverse)?;
+                }
+                return Ok(());
+            }
+            None => {}
+        }
+        match &self.perforce {
+            Some(perforce) => {
+                write!(f, "{}", perforce)?;
+                // `p4 describe` writes an empty line before the hunks
+                if perforce.new_path.is_none() && !self.hunks.is_empty() {
+                    writeln!(f)?;
+                }
+            }
+            None => {
+                write!(f, "--- {}", self.old)?;
+                write!(f, "\n+++ {}", self.new)?;
+            }
+        }
+        if let Some((last, hunks)) = self.hunks.split_last() {
+            for hunk in hunks {
+                write!(f, "\n{}", hunk)?;
+            }
+            write!(
+                f,
+                "\n@@ -{} +{} @@{}",
+                last.old_range, last.new_range, last.range_hint
+            )?;
+            let end_newlines = (self.old_end_newline, self.new_end_newline);
+            fmt_last_lines(f, &last.lines, end_newlines, "file")?;
+        }
+
+        if let Some(svn) = self
+            .svn
+            .as_ref()
+            .filter(|svn| !svn.property_changes.is_empty())
+        {
+            write!(
+                f,
+                "\n\nProperty changes on: {}\n{}",
+                svn.path,
+                "_".repeat(67)
+            )?;
+            for change in &svn.property_changes {
+                writeln!(f)?;
+                change.fmt_lines(f)?;
+            }
         }
         Ok(())
     }
 }
 
+// Writes the lines of the last hunk of a file or property. The last line of each side that
+// doesn't end in a newline is followed by an indicator.
+fn fmt_last_lines(
+    f: &mut dyn fmt::Write,
+    lines: &[Line<'_>],
+    (old_end_newline, new_end_newline): (bool, bool),
+    what: &str,
+) -> fmt::Result {
+    let last_old = lines.iter().rposition(|l| !matches!(l, Line::Add(_)));
+    let last_new = lines.iter().rposition(|l| !matches!(l, Line::Remove(_)));
+    for (i, line) in lines.iter().enumerate() {
+        write!(f, "\n{}", line)?;
+        if (Some(i) == last_old && !old_end_newline) || (Some(i) == last_new && !new_end_newline) {
+            write!(f, "\n\\ No newline at end of {}", what)?;
+        }
+    }
+    Ok(())
+}
+
+/// Where a [`Patch`] was found in the text it was parsed from
+///
+/// The span covers the patch's own lines, from its `diff --git` or `---` line to its last hunk
+/// line, without any preamble before it or empty lines after it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Span {
+    /// The byte offset of the start of the patch
+    pub start: usize,
+    /// The byte offset just past the end of the patch, including its last line ending
+    pub end: usize,
+    /// The line number of the first line of the patch, starting at 1
+    pub start_line: u32,
+    /// The line number of the last line of the patch
+    pub end_line: u32,
+}
+
+/// The characters that end each line of a [`Patch`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LineEnding {
+    /// `\n`, as written by diff tools on Unix
+    Lf,
+    /// `\r\n`, as written by diff tools on Windows
+    CrLf,
+}
+
+impl LineEnding {
+    /// The line ending as a string, e.g. for ending the last line after formatting a [`Patch`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{LineEnding, Patch};
+    /// let sample = "--- old.txt\r\n+++ new.txt\r\n@@ -1,1 +1,1 @@\r\n-a\r\n+b\r\n";
+    /// let patch = Patch::from_single(sample)?;
+    /// assert_eq!(patch.line_ending, LineEnding::CrLf);
+    /// assert_eq!(format!("{}{}", patch, patch.line_ending.as_str()), sample);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
 impl<'a> Patch<'a> {
     #[allow(clippy::tabs_in_doc_comments)]
     /// Attempt to parse a patch from the given string.
@@ -72,12 +280,13 @@ impl<'a> Patch<'a> {
     /// let patch = Patch::from_single(sample)?;
     /// assert_eq!(&patch.old.path, "lao");
     /// assert_eq!(&patch.new.path, "tzu");
-    /// assert_eq!(patch.end_newline, false);
+    /// assert_eq!(patch.old_end_newline, true);
+    /// assert_eq!(patch.new_end_newline, false);
     /// # Ok(())
     /// # }
     /// ```
     pub fn from_single(s: &'a str) -> Result<Self, ParseError<'a>> {
-        parse_single_patch(s)
+        parse_single_patch(s, &ParserOptions::default())
     }
 
     /// Attempt to parse as many patches as possible from the given string. This is useful for when
@@ -131,26 +340,928 @@ impl<'a> Patch<'a> {
     /// # }
     /// ```
     pub fn from_multiple(s: &'a str) -> Result<Vec<Self>, ParseError<'a>> {
-        parse_multiple_patches(s)
+        parse_multiple_patches(s, &ParserOptions::default())
+    }
+
+    /// Like [`Patch::from_multiple`], but parsing one patch at a time as the iterator is advanced,
+    /// so that it can stop early without parsing the rest of a huge input
+    ///
+    /// The iterator ends after the first error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use patch::Patch;
+    /// let sample = "\
+    /// --- a/Cargo.lock
+    /// +++ b/Cargo.lock
+    /// @@ -1,1 +1,1 @@
+    /// -version = 3
+    /// +version = 4
+    /// --- a/src/main.rs
+    /// +++ b/src/main.rs
+    /// @@ -1,1 +1,1 @@
+    /// -not a valid hunk\n";
+    ///
+    /// let touches_lockfile = Patch::iter_multiple(sample)
+    ///     .any(|patch| patch.is_ok_and(|patch| patch.new.path == "b/Cargo.lock"));
+    /// assert!(touches_lockfile);
+    /// assert!(Patch::iter_multiple(sample).nth(1).unwrap().is_err());
+    /// ```
+    pub fn iter_multiple(s: &'a str) -> impl Iterator<Item = Result<Self, ParseError<'a>>> + 'a {
+        iter_multiple_patches(s, &ParserOptions::default())
+    }
+
+    /// Like [`Patch::iter_multiple`], but with the given [`ParserOptions`] instead of the default
+    /// ones, e.g. to stop at the limits for untrusted input
+    pub fn iter_multiple_with_options(
+        s: &'a str,
+        options: &ParserOptions,
+    ) -> impl Iterator<Item = Result<Self, ParseError<'a>>> + 'a {
+        iter_multiple_patches(s, options)
+    }
+
+    /// Like [`Patch::from_multiple_with_options`], but also returns [`Warning`]s about what the
+    /// parser worked around, like skipped text or hunks whose line counts don't match, in the
+    /// order they appear in the input
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{ParserOptions, Patch, WarningKind};
+    /// let sample = "\
+    /// Some notes
+    /// --- before.py
+    /// +++ after.py
+    /// @@ -1,3 +1,3 @@
+    /// -bacon
+    /// +python\n";
+    ///
+    /// let (patches, warnings) =
+    ///     Patch::from_multiple_with_warnings(sample, &ParserOptions::forgiving())?;
+    /// assert_eq!(patches.len(), 1);
+    /// let kinds: Vec<_> = warnings.iter().map(|warning| warning.kind).collect();
+    /// assert_eq!(kinds, [WarningKind::Preamble, WarningKind::HunkLineCount]);
+    /// assert_eq!(warnings[1].span.start_line, 4);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_multiple_with_warnings(
+        s: &'a str,
+        options: &ParserOptions,
+    ) -> Result<(Vec<Self>, Vec<Warning>), ParseError<'a>> {
+        parse_multiple_patches_with_warnings(s, options)
+    }
+
+    /// Parse only the file and hunk headers of the patches in the given string, skipping the
+    /// lines of each hunk
+    ///
+    /// This is faster than [`Patch::from_multiple`] and doesn't allocate for each line, for tools
+    /// that only need to know which files and lines a large patch touches. The hunk lines are
+    /// still checked against the ranges, to know where each hunk ends.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{Patch, Range};
+    /// let sample = "\
+    /// --- before.py
+    /// +++ after.py
+    /// @@ -1,2 +1,2 @@ def main():
+    /// -bacon
+    /// +python
+    ///  guido\n";
+    ///
+    /// let patches = Patch::scan_headers(sample)?;
+    /// assert_eq!(patches[0].new.path, "after.py");
+    /// assert_eq!(patches[0].hunks[0].new_range, Range { start: 1, count: 2 });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn scan_headers(s: &'a str) -> Result<Vec<PatchHeaders<'a>>, ParseError<'a>> {
+        parse_patch_headers(s, &ParserOptions::default())
+    }
+
+    /// Like [`Patch::scan_headers`], but with the given [`ParserOptions`] instead of the default
+    /// ones
+    pub fn scan_headers_with_options(
+        s: &'a str,
+        options: &ParserOptions,
+    ) -> Result<Vec<PatchHeaders<'a>>, ParseError<'a>> {
+        parse_patch_headers(s, options)
+    }
+
+    /// Like [`Patch::from_single`], but with the given [`ParserOptions`] instead of the default
+    /// ones, e.g. to validate that a patch is a plain unified diff
+    pub fn from_single_with_options(
+        s: &'a str,
+        options: &ParserOptions,
+    ) -> Result<Self, ParseError<'a>> {
+        parse_single_patch(s, options)
+    }
+
+    /// Like [`Patch::from_multiple`], but with the given [`ParserOptions`] instead of the default
+    /// ones
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{ParserOptions, Patch};
+    /// // The hunk says it has 3 lines, but only 2 made it into the file
+    /// let sample = "\
+    /// --- before.py
+    /// +++ after.py
+    /// @@ -1,3 +1,3 @@
+    /// -bacon
+    /// +python\n";
+    ///
+    /// assert!(Patch::from_multiple(sample).is_err());
+    /// let patches = Patch::from_multiple_with_options(sample, &ParserOptions::forgiving())?;
+    /// assert_eq!(patches[0].hunks[0].lines.len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_multiple_with_options(
+        s: &'a str,
+        options: &ParserOptions,
+    ) -> Result<Vec<Self>, ParseError<'a>> {
+        parse_multiple_patches(s, options)
+    }
+
+    /// Like [`Patch::from_multiple`], but also returns where each patch was found in the input,
+    /// e.g. to show the raw text of a single file's patch
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::Patch;
+    /// let sample = "\
+    /// Some preamble text
+    /// --- a.txt
+    /// +++ a.txt
+    /// @@ -1,1 +1,1 @@
+    /// -a
+    /// +A
+    /// --- b.txt
+    /// +++ b.txt
+    /// @@ -1,1 +1,1 @@
+    /// -b
+    /// +B\n";
+    ///
+    /// let patches = Patch::from_multiple_with_spans(sample)?;
+    /// let (patch, span) = &patches[1];
+    /// assert_eq!(patch.new.path, "b.txt");
+    /// assert_eq!((span.start_line, span.end_line), (7, 11));
+    /// assert!(sample[span.start..span.end].starts_with("--- b.txt\n"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_multiple_with_spans(s: &'a str) -> Result<Vec<(Self, Span)>, ParseError<'a>> {
+        parse_multiple_patches_with_spans(s, &ParserOptions::default())
+    }
+
+    /// Like [`Patch::from_multiple_with_spans`], but with the given [`ParserOptions`] instead of
+    /// the default ones
+    pub fn from_multiple_with_spans_with_options(
+        s: &'a str,
+        options: &ParserOptions,
+    ) -> Result<Vec<(Self, Span)>, ParseError<'a>> {
+        parse_multiple_patches_with_spans(s, options)
+    }
+
+    /// Like [`Patch::from_multiple`], but skips the patches that fail to parse instead of failing
+    /// entirely, e.g. to salvage the good files of a large diff
+    ///
+    /// Returns the patches that were parsed, and the span and error of each patch that wasn't.
+    /// A skipped patch runs until the next `diff --git` line (or `---` line if the broken patch
+    /// doesn't start with `diff --git`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use patch::Patch;
+    /// let sample = "\
+    /// --- a.txt
+    /// +++ a.txt
+    /// @@ -1,one +1,one @@
+    /// -a
+    /// +A
+    /// --- b.txt
+    /// +++ b.txt
+    /// @@ -1,1 +1,1 @@
+    /// -b
+    /// +B\n";
+    ///
+    /// let (patches, errors) = Patch::from_multiple_lossy(sample);
+    /// assert_eq!(patches.len(), 1);
+    /// assert_eq!(patches[0].old.path, "b.txt");
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!((errors[0].0.start_line, errors[0].0.end_line), (1, 5));
+    /// ```
+    pub fn from_multiple_lossy(s: &'a str) -> (Vec<Self>, Vec<(Span, ParseError<'a>)>) {
+        parse_multiple_patches_lossy(s, &ParserOptions::default())
+    }
+
+    /// Like [`Patch::from_multiple_lossy`], but with the given [`ParserOptions`] instead of the
+    /// default ones
+    ///
+    /// Going over one of the limits isn't skipped like a broken patch: it stops parsing, and its
+    /// error spans the rest of the input.
+    pub fn from_multiple_lossy_with_options(
+        s: &'a str,
+        options: &ParserOptions,
+    ) -> (Vec<Self>, Vec<(Span, ParseError<'a>)>) {
+        parse_multiple_patches_lossy(s, options)
+    }
+
+    /// Parse the patches in the given string up to the first one that fails, e.g. because the
+    /// input was cut off while downloading it
+    ///
+    /// Returns the complete patches before that point, and the error that stopped parsing, if
+    /// any. Unlike [`Patch::from_multiple_lossy`], nothing after the error is parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use patch::Patch;
+    /// let sample = "\
+    /// --- a.txt
+    /// +++ a.txt
+    /// @@ -1,1 +1,1 @@
+    /// -a
+    /// +A
+    /// --- b.txt
+    /// +++ b.txt
+    /// @@ -1,3 +1,3 @@
+    /// -b\n";
+    ///
+    /// let (patches, error) = Patch::from_multiple_partial(sample);
+    /// assert_eq!(patches.len(), 1);
+    /// assert_eq!(patches[0].old.path, "a.txt");
+    /// assert!(error.is_some());
+    /// ```
+    pub fn from_multiple_partial(s: &'a str) -> (Vec<Self>, Option<ParseError<'a>>) {
+        parse_multiple_patches_partial(s, &ParserOptions::default())
+    }
+
+    /// Like [`Patch::from_multiple_partial`], but with the given [`ParserOptions`] instead of the
+    /// default ones
+    pub fn from_multiple_partial_with_options(
+        s: &'a str,
+        options: &ParserOptions,
+    ) -> (Vec<Self>, Option<ParseError<'a>>) {
+        parse_multiple_patches_partial(s, options)
+    }
+
+    /// Attempt to parse the output of a recursive `diff -r`, which can have lines about files
+    /// that only one side has, or that weren't compared, between the patches
+    ///
+    /// [`Patch::from_multiple`] reads those lines as part of the next patch's
+    /// [`Patch::preamble`], and fails on them after the last patch.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{DiffEntry, Patch};
+    /// let sample = "\
+    /// Only in new/docs: guide.txt
+    /// diff -ruN old/notes.txt new/notes.txt
+    /// --- old/notes.txt
+    /// +++ new/notes.txt
+    /// @@ -1 +1 @@
+    /// -hello
+    /// +goodbye
+    /// Files old/logo.svg and new/logo.svg differ\n";
+    ///
+    /// let entries = Patch::from_recursive_diff(sample)?;
+    /// assert_eq!(entries.len(), 3);
+    /// assert_eq!(entries[0], DiffEntry::OnlyIn { dir: "new/docs", name: "guide.txt" });
+    /// assert!(matches!(&entries[1], DiffEntry::Patch(patch) if patch.new.path == "new/notes.txt"));
+    /// assert!(matches!(&entries[2], DiffEntry::BinaryDiffer { old, .. } if old == "old/logo.svg"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_recursive_diff(s: &'a str) -> Result<Vec<DiffEntry<'a>>, ParseError<'a>> {
+        parse_recursive_diff(s, &ParserOptions::default())
+    }
+
+    /// Like [`Patch::from_recursive_diff`], but with the given [`ParserOptions`] instead of the
+    /// default ones. The `Only in` and `Files differ` lines are always accepted.
+    pub fn from_recursive_diff_with_options(
+        s: &'a str,
+        options: &ParserOptions,
+    ) -> Result<Vec<DiffEntry<'a>>, ParseError<'a>> {
+        parse_recursive_diff(s, options)
+    }
+
+    /// Attempt to parse one or more hunks without the `---` and `+++` header lines, like a
+    /// snippet from a code review comment, as a patch of the given files
+    ///
+    /// Empty lines around the hunks are ignored, like in [`Hunk::from_fragment`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::Patch;
+    /// let snippet = "\
+    /// @@ -1,2 +1,2 @@
+    ///  fn main() {
+    /// -    println!(\"hi\");
+    /// +    println!(\"hello\");\n";
+    ///
+    /// let patch = Patch::from_headerless(snippet, "src/main.rs", "src/main.rs")?;
+    /// assert_eq!(patch.new.path, "src/main.rs");
+    /// assert_eq!(patch.hunks[0].lines.len(), 3);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_headerless(
+        s: &'a str,
+        old_path: &'a str,
+        new_path: &'a str,
+    ) -> Result<Self, ParseError<'a>> {
+        parse_headerless_patch(s, old_path, new_path)
+    }
+
+    /// Attempt to parse the default output format of `diff`, with commands like `3,4c3` followed
+    /// by the removed lines starting with `<` and the added lines starting with `>`, as a patch
+    /// of the given files
+    ///
+    /// Each command becomes a hunk without context lines, with the ranges a unified diff would
+    /// have.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{Line, Patch};
+    /// let sample = "\
+    /// 2c2
+    /// < bacon
+    /// ---
+    /// > python
+    /// 4a5
+    /// > guido\n";
+    ///
+    /// let patch = Patch::from_normal(sample, "before.py", "after.py")?;
+    /// assert_eq!(patch.hunks[0].lines, [Line::Remove("bacon"), Line::Add("python")]);
+    /// assert_eq!(patch.hunks[1].to_string(), "@@ -4,0 +5,1 @@\n+guido");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_normal(
+        s: &'a str,
+        old_path: &'a str,
+        new_path: &'a str,
+    ) -> Result<Self, ParseError<'a>> {
+        parse_normal_patch(s, old_path, new_path)
+    }
+
+    /// Attempt to parse the raw output of `git diff`, `git show` or `git log -p`, e.g. as captured
+    /// from a subprocess. Unlike [`Patch::from_multiple`], this accepts output without any
+    /// patches, like that of `git diff` with no changes.
+    ///
+    /// The output must be valid UTF-8. Color codes, e.g. from `--color=always`, are removed by
+    /// moving the rest of the output over them, so the patches can borrow from `bytes` without
+    /// allocating. Line numbers and offsets in errors are those of the output without the color
+    /// codes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use patch::Patch;
+    /// let mut output = b"\
+    /// commit 0bc302b2c6b79b3e5a7b1e2d03c8aa5a2e3f2d1c
+    /// Author: phil <uniphil@gmail.com>
+    /// Date:   Sat Oct 16 12:00:00 2021 -0400
+    ///
+    ///     Fix the greeting
+    ///
+    /// diff --git a/hello.txt b/hello.txt
+    /// index c7921f5..8946660 100644
+    /// --- a/hello.txt
+    /// +++ b/hello.txt
+    /// @@ -1 +1 @@
+    /// \x1b[31m-hello\x1b[m
+    /// \x1b[32m+goodbye\x1b[m
+    /// ".to_vec();
+    ///
+    /// let patches = Patch::from_git_diff_output(&mut output).unwrap();
+    /// assert_eq!(patches.len(), 1);
+    /// assert_eq!(patches[0].hunks[0].new_text(), "goodbye\n");
+    /// assert!(Patch::from_git_diff_output(&mut []).unwrap().is_empty());
+    /// ```
+    pub fn from_git_diff_output(bytes: &'a mut [u8]) -> Result<Vec<Self>, ParseError<'a>> {
+        parse_git_diff_output(bytes, &ParserOptions::default())
+    }
+
+    /// Like [`Patch::from_git_diff_output`], but with the given [`ParserOptions`] instead of the
+    /// default ones
+    ///
+    /// The commit headers are text before the patches, so the options must allow junk for output
+    /// with commits, like that of `git show`.
+    pub fn from_git_diff_output_with_options(
+        bytes: &'a mut [u8],
+        options: &ParserOptions,
+    ) -> Result<Vec<Self>, ParseError<'a>> {
+        parse_git_diff_output(bytes, options)
+    }
+
+    /// The `#` comment lines in the preamble, like the notes quilt and many maintainers keep
+    /// above each patch, without the `#` and the space after it
+    ///
+    /// Comments stay in [`Patch::preamble`], so `Display` still writes them before the patch.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::Patch;
+    /// let sample = "\
+    /// ## Fix the greeting
+    /// ##
+    /// ## Upstream: no
+    /// --- before.py
+    /// +++ after.py
+    /// @@ -1,1 +1,1 @@
+    /// -bacon
+    /// +python\n";
+    ///
+    /// let patch = Patch::from_single(sample)?;
+    /// assert_eq!(patch.comments(), ["Fix the greeting", "", "Upstream: no"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn comments(&self) -> Vec<&'a str> {
+        self.preamble
+            .into_iter()
+            .flat_map(str::lines)
+            .filter_map(|line| line.strip_prefix('#'))
+            .map(|comment| comment.strip_prefix(' ').unwrap_or(comment))
+            .collect()
+    }
+
+    /// The DEP-3 fields in the [`Patch::preamble`] of a Debian patch, or `None` if it has none
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::Patch;
+    /// let sample = "\
+    /// Description: Use the system's zlib
+    /// Forwarded: not-needed
+    /// Last-Update: 2021-10-16
+    /// ---
+    /// --- a/Makefile
+    /// +++ b/Makefile
+    /// @@ -1,1 +1,1 @@
+    /// -ZLIB = vendor/zlib
+    /// +ZLIB = /usr/lib\n";
+    ///
+    /// let patch = Patch::from_single(sample)?;
+    /// let headers = patch.dep3_headers().unwrap();
+    /// assert_eq!(headers.description(), Some("Use the system's zlib"));
+    /// assert_eq!(headers.last_update(), Some("2021-10-16"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dep3_headers(&self) -> Option<Dep3Headers<'a>> {
+        let headers = Dep3Headers::parse(self.preamble?);
+        if headers.fields.is_empty() {
+            None
+        } else {
+            Some(headers)
+        }
+    }
+
+    /// Whether this patch creates, deletes, or modifies its file
+    ///
+    /// This comes from git's `new file mode` and `deleted file mode` lines, or otherwise from an
+    /// old or new path of `/dev/null` (see [`File::is_null`]).
+    pub fn kind(&self) -> FileChangeKind {
+        match &self.git {
+            Some(git) if git.kind != FileChangeKind::Modified => git.kind,
+            _ if self.old.is_null() => FileChangeKind::Created,
+            _ if self.new.is_null() => FileChangeKind::Deleted,
+            _ => FileChangeKind::Modified,
+        }
+    }
+
+    /// True if this patch creates its file, see [`Patch::kind`]
+    pub fn is_creation(&self) -> bool {
+        self.kind() == FileChangeKind::Created
+    }
+
+    /// True if this patch deletes its file, see [`Patch::kind`]
+    pub fn is_deletion(&self) -> bool {
+        self.kind() == FileChangeKind::Deleted
+    }
+
+    /// Format this patch as a context diff, like `diff -c` writes, for tools that don't accept
+    /// unified diffs
+    ///
+    /// Only the file headers and hunks are written, not git's extended headers or the preamble.
+    /// Like `Display`, the output has no line ending after its last line.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::Patch;
+    /// let sample = "\
+    /// --- before.py
+    /// +++ after.py
+    /// @@ -1,3 +1,4 @@
+    ///  import os
+    /// -bacon
+    /// +python
+    /// +guido
+    ///  print()\n";
+    ///
+    /// let patch = Patch::from_single(sample)?;
+    /// let expected = "\
+    /// *** before.py
+    /// --- after.py
+    /// ***************
+    /// *** 1,3 ****
+    ///   import os
+    /// ! bacon
+    ///   print()
+    /// --- 1,4 ----
+    ///   import os
+    /// ! python
+    /// ! guido
+    ///   print()";
+    /// assert_eq!(patch.to_context_format(), expected);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_context_format(&self) -> String {
+        context_format(self)
+    }
+
+    /// A short summary of what this patch does, e.g. `modifies src/lib.rs: replaces 3 lines in
+    /// function bar, adds 10 lines at line 80` or `creates tests/baz.rs with 12 lines`. Handy as
+    /// default text for bot comments or commit message suggestions.
+    ///
+    /// Hunks are located by their [`Hunk::scope`] if it is known. Paths are written as they are
+    /// in the patch, so use [`Patch::strip_prefixes`] first to leave out git's `a/` and `b/`.
+    pub fn describe(&self) -> String {
+        describe(self)
+    }
+
+    /// The number of lines this patch adds and removes, like a line of `git diff --numstat`
+    pub fn stat(&self) -> FileStat<'a> {
+        let path = if self.is_deletion() {
+            &self.old.path
+        } else {
+            &self.new.path
+        };
+        let lines = self.hunks.iter().flat_map(|hunk| &hunk.lines);
+        FileStat {
+            path: path.clone(),
+            additions: lines.clone().filter(|l| matches!(l, Line::Add(_))).count() as u64,
+            deletions: lines.filter(|l| matches!(l, Line::Remove(_))).count() as u64,
+            binary: self.binary.is_some(),
+        }
+    }
+
+    /// Remove the `a/` and `b/` prefixes git adds to paths, so `old.path` and `new.path` are the
+    /// paths in the repository. git's mnemonic prefixes (`c/`, `i/`, `w/` and `o/`) are removed
+    /// as well. The paths are then also formatted without them.
+    ///
+    /// Nothing is changed unless both paths have a different prefix, so patches made with
+    /// `git diff --no-prefix` keep directories named `a` or `b`. `/dev/null` is left as it is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::Patch;
+    /// let sample = "\
+    /// diff --git a/src/lib.rs b/src/lib.rs
+    /// --- a/src/lib.rs
+    /// +++ b/src/lib.rs
+    /// @@ -1 +1 @@
+    /// -bacon
+    /// +python\n";
+    ///
+    /// let mut patch = Patch::from_single(sample)?;
+    /// patch.strip_prefixes();
+    /// assert_eq!(patch.old.path, "src/lib.rs");
+    /// assert_eq!(patch.new.path, "src/lib.rs");
+    /// assert!(patch.to_string().starts_with("diff --git src/lib.rs src/lib.rs\n"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn strip_prefixes(&mut self) {
+        fn prefix(path: &str) -> Option<u8> {
+            match path.as_bytes() {
+                [prefix @ (b'a' | b'b' | b'c' | b'i' | b'w' | b'o'), b'/', ..] => Some(*prefix),
+                _ => None,
+            }
+        }
+        fn strip(path: &mut Cow<'_, str>) {
+            if prefix(path).is_some() {
+                match path {
+                    Cow::Borrowed(borrowed) => *borrowed = &borrowed[2..],
+                    Cow::Owned(owned) => {
+                        owned.drain(..2);
+                    }
+                }
+            }
+        }
+
+        // A `/dev/null` side has no prefix to compare
+        let old = (!self.old.is_null()).then(|| prefix(&self.old.path));
+        let new = (!self.new.is_null()).then(|| prefix(&self.new.path));
+        let prefixed = match (old, new) {
+            (Some(Some(old)), Some(Some(new))) => old != new,
+            (Some(Some(_)), None) | (None, Some(Some(_))) => true,
+            _ => false,
+        };
+        if !prefixed {
+            return;
+        }
+        for file in [&mut self.old, &mut self.new] {
+            if !file.is_null() {
+                strip(&mut file.path);
+            }
+        }
+        if let Some(git) = &mut self.git {
+            strip(&mut git.old_path);
+            strip(&mut git.new_path);
+        }
+    }
+
+    /// Remove a trailing `\r` from every line of every hunk
+    ///
+    /// When the patch uses LF line endings, lines of a file with CRLF line endings keep their `\r`,
+    /// so that the patch is formatted the same way it was parsed. Use this to compare the text
+    /// without it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{Line, Patch};
+    /// let sample = "\
+    /// --- before.py
+    /// +++ after.py
+    /// @@ -1,1 +1,1 @@
+    /// -bacon\r
+    /// +python\r\n";
+    ///
+    /// let mut patch = Patch::from_single(sample)?;
+    /// assert_eq!(patch.hunks[0].lines[0], Line::Remove("bacon\r"));
+    /// patch.strip_carriage_returns();
+    /// assert_eq!(patch.hunks[0].lines[0], Line::Remove("bacon"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn strip_carriage_returns(&mut self) {
+        for line in self.hunks.iter_mut().flat_map(|hunk| &mut hunk.lines) {
+            let (Line::Add(text) | Line::Remove(text) | Line::Context(text)) = line;
+            *text = text.strip_suffix('\r').unwrap_or(text);
+        }
+    }
+
+    /// The commits a submodule changes between, if this patch changes a submodule
+    ///
+    /// git writes these as a hunk whose only lines are `-Subproject commit <sha>` and
+    /// `+Subproject commit <sha>`. `hunks` still holds that hunk, so the patch is formatted the
+    /// same way it was parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::Patch;
+    /// let sample = "\
+    /// diff --git a/sub b/sub
+    /// index 032a065..f17ae85 160000
+    /// --- a/sub
+    /// +++ b/sub
+    /// @@ -1 +1 @@
+    /// -Subproject commit 032a065a41b6ef01731b0215289633cdac04b53f
+    /// +Subproject commit f17ae851a67e84a0748a47d16a7c7977e8b1eff4\n";
+    ///
+    /// let patch = Patch::from_single(sample)?;
+    /// let submodule = patch.submodule().unwrap();
+    /// assert_eq!(submodule.old_commit, Some("032a065a41b6ef01731b0215289633cdac04b53f"));
+    /// assert_eq!(submodule.new_commit, Some("f17ae851a67e84a0748a47d16a7c7977e8b1eff4"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn submodule(&self) -> Option<SubmoduleChange<'a>> {
+        // git uses this mode for submodules, a.k.a. gitlinks
+        const GITLINK: Option<u32> = Some(0o160000);
+        if let Some(git) = &self.git {
+            if git.old_mode != GITLINK && git.new_mode != GITLINK {
+                return None;
+            }
+        }
+        let hunk = match self.hunks.as_slice() {
+            [hunk] => hunk,
+            _ => return None,
+        };
+
+        fn commit(line: &str) -> Option<&str> {
+            let commit = line.strip_prefix("Subproject commit ")?;
+            // git marks submodules with uncommitted changes in the working tree
+            let commit = commit.strip_suffix("-dirty").unwrap_or(commit);
+            if commit.is_empty() || !commit.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            Some(commit)
+        }
+        let mut change = SubmoduleChange {
+            old_commit: None,
+            new_commit: None,
+        };
+        for line in &hunk.lines {
+            match *line {
+                Line::Remove(line) if change.old_commit.is_none() => {
+                    change.old_commit = Some(commit(line)?)
+                }
+                Line::Add(line) if change.new_commit.is_none() => {
+                    change.new_commit = Some(commit(line)?)
+                }
+                _ => return None,
+            }
+        }
+        Some(change)
+    }
+
+    /// The complete old file, if this patch contains all of it, e.g. because it deletes the file
+    ///
+    /// This is only known for patches that create or delete their file (see [`Patch::kind`]).
+    /// For other patches, an error lists the lines that aren't in any hunk.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::Patch;
+    /// let sample = "\
+    /// --- /dev/null
+    /// +++ b/hello.txt
+    /// @@ -0,0 +1,2 @@
+    /// +hello
+    /// +world\n";
+    ///
+    /// let patch = Patch::from_single(sample)?;
+    /// assert_eq!(patch.old_text(), Ok(String::new()));
+    /// assert_eq!(patch.new_text(), Ok("hello\nworld\n".to_string()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn old_text(&self) -> Result<String, IncompleteFileError> {
+        self.side_text(
+            FileChangeKind::Deleted,
+            FileChangeKind::Created,
+            |hunk| &hunk.old_range,
+            Hunk::old_text,
+            self.old_end_newline,
+        )
+    }
+
+    /// The complete new file, if this patch contains all of it, e.g. because it creates the file
+    ///
+    /// See [`Patch::old_text`].
+    pub fn new_text(&self) -> Result<String, IncompleteFileError> {
+        self.side_text(
+            FileChangeKind::Created,
+            FileChangeKind::Deleted,
+            |hunk| &hunk.new_range,
+            Hunk::new_text,
+            self.new_end_newline,
+        )
+    }
+
+    fn side_text(
+        &self,
+        complete: FileChangeKind,
+        empty: FileChangeKind,
+        range: impl for<'h> Fn(&'h Hunk<'a>) -> &'h Range,
+        text: impl Fn(&Hunk<'a>) -> String,
+        end_newline: bool,
+    ) -> Result<String, IncompleteFileError> {
+        let kind = self.kind();
+        if kind == empty {
+            return Ok(String::new());
+        }
+
+        let mut result = String::new();
+        let mut gaps = Vec::new();
+        let mut next_line = 1;
+        for hunk in &self.hunks {
+            let range = range(hunk);
+            if range.count == 0 {
+                continue;
+            }
+            if range.start > next_line {
+                gaps.push(Range {
+                    start: next_line,
+                    count: range.start - next_line,
+                });
+            }
+            result.push_str(&text(hunk));
+            next_line = range.start + range.count;
+        }
+        let unknown_after = if kind == complete {
+            None
+        } else {
+            Some(next_line)
+        };
+        if !gaps.is_empty() || unknown_after.is_some() || self.binary.is_some() {
+            return Err(IncompleteFileError {
+                gaps,
+                unknown_after,
+            });
+        }
+        if !end_newline {
+            result.pop();
+        }
+        Ok(result)
+    }
+}
+
+/// Returned by [`Patch::old_text`] and [`Patch::new_text`] when a patch doesn't contain its whole
+/// file
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct IncompleteFileError {
+    /// The lines before and between hunks that are missing from the patch
+    pub gaps: Vec<Range>,
+    /// The first line after the last hunk, if the file may have more lines from there on
+    pub unknown_after: Option<u64>,
+}
+
+impl fmt::Display for IncompleteFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Patch doesn't contain the whole file")?;
+        let mut separator = ":";
+        for gap in &self.gaps {
+            let end = gap.start + gap.count - 1;
+            write!(f, "{} lines {}-{} are missing", separator, gap.start, end)?;
+            separator = ",";
+        }
+        if let Some(line) = self.unknown_after {
+            write!(f, "{} lines from {} on may be missing", separator, line)?;
+        }
+        Ok(())
     }
 }
 
+impl Error for IncompleteFileError {}
+
+/// The commits a submodule changes between, from [`Patch::submodule`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SubmoduleChange<'a> {
+    /// The commit the submodule pointed to before, or `None` if the submodule is added
+    pub old_commit: Option<&'a str>,
+    /// The commit the submodule points to after, or `None` if the submodule is removed
+    ///
+    /// A `-dirty` suffix, which git adds when the submodule has uncommitted changes, is removed.
+    pub new_commit: Option<&'a str>,
+}
+
 /// Check if a string needs to be quoted, and format it accordingly
-fn maybe_escape_quote(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
-    let quote = s
-        .chars()
-        .any(|ch| matches!(ch, ' ' | '\t' | '\r' | '\n' | '\"' | '\0' | '\\'));
+///
+/// Non-ASCII characters are only escaped in octal if `escape_non_ascii` is set, for paths that
+/// were read with them escaped, like git writes them with its default `core.quotePath` setting.
+fn maybe_escape_quote(f: &mut dyn fmt::Write, s: &str, escape_non_ascii: bool) -> fmt::Result {
+    let escape_non_ascii = escape_non_ascii && !s.is_ascii();
+    let quote = escape_non_ascii
+        || s.chars()
+            .any(|ch| matches!(ch, ' ' | '\"' | '\\') || ch.is_control());
 
     if quote {
         write!(f, "\"")?;
-        for ch in s.chars() {
+        let mut chars = s.chars().peekable();
+        while let Some(ch) = chars.next() {
             match ch {
+                // `\0` followed by digits would be read as an octal escape
+                '\0' if chars.peek().is_some_and(char::is_ascii_digit) => write!(f, r"\000")?,
                 '\0' => write!(f, r"\0")?,
                 '\n' => write!(f, r"\n")?,
                 '\r' => write!(f, r"\r")?,
                 '\t' => write!(f, r"\t")?,
                 '"' => write!(f, r#"\""#)?,
                 '\\' => write!(f, r"\\")?,
+                // Like git, escape each byte of other special characters in octal
+                _ if ch.is_control() || (escape_non_ascii && !ch.is_ascii()) => {
+                    for byte in ch.encode_utf8(&mut [0; 4]).bytes() {
+                        write!(f, "\\{:03o}", byte)?;
+                    }
+                }
                 _ => write!(f, "{}", ch)?,
             }
         }
@@ -166,22 +1277,57 @@ pub struct File<'a> {
     /// The parsed path or file name of the file
     ///
     /// Avoids allocation if at all possible. Only allocates if the file path is a quoted string
-    /// literal. String literals are necessary in some cases, for example if the file path has
-    /// spaces in it. These literals can contain escaped characters which are initially seen as
-    /// groups of two characters by the parser (e.g. '\\' + 'n'). A newly allocated string is
-    /// used to unescape those characters (e.g. "\\n" -> '\n').
+    /// literal containing escaped characters. String literals are necessary in some cases, for
+    /// example if the file path has special characters in it. Their escaped characters are
+    /// initially seen as groups of two characters by the parser (e.g. '\\' + 'n'). A newly
+    /// allocated string is used to unescape those characters (e.g. "\\n" -> '\n').
     ///
     /// **Note:** While this string is typically a file path, this library makes no attempt to
     /// verify the format of that path. That means that **this field can potentially be any
     /// string**. You should verify it before doing anything that may be security-critical.
     pub path: Cow<'a, str>,
+    /// Whether the non-ASCII characters of the path were escaped in a quoted string, like git
+    /// writes them with its default `core.quotePath` setting
+    ///
+    /// `Display` escapes them again if set, and otherwise writes them as they are.
+    pub escaped: bool,
     /// Any additional information provided with the file path
     pub meta: Option<FileMetadata<'a>>,
 }
 
+impl<'a> File<'a> {
+    #[allow(clippy::tabs_in_doc_comments)]
+    /// Attempt to parse a single `---` or `+++` header line, with or without its line ending.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::File;
+    /// let file = File::parse_header_line("+++ tzu	2002-02-21 23:30:50.442260588 -0800\n")?;
+    /// assert_eq!(&file.path, "tzu");
+    /// assert!(file.meta.is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_header_line(s: &'a str) -> Result<Self, ParseError<'a>> {
+        parse_header_line(s)
+    }
+
+    /// True if this is the `/dev/null` path used for the missing side of a created or deleted
+    /// file, also with a prefix like `a//dev/null` as written by some tools
+    pub fn is_null(&self) -> bool {
+        let path: &str = &self.path;
+        path == "/dev/null"
+            || path
+                .split_once('/')
+                .is_some_and(|(prefix, rest)| !prefix.is_empty() && rest == "/dev/null")
+    }
+}
+
 impl<'a> fmt::Display for File<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        maybe_escape_quote(f, &self.path)?;
+        maybe_escape_quote(f, &self.path, self.escaped)?;
         if let Some(meta) = &self.meta {
             write!(f, "\t{}", meta)?;
         }
@@ -204,11 +1350,386 @@ impl<'a> fmt::Display for FileMetadata<'a> {
             FileMetadata::DateTime(datetime) => {
                 write!(f, "{}", datetime.format("%F %T%.f %z"))
             }
-            FileMetadata::Other(data) => maybe_escape_quote(f, data),
+            FileMetadata::Other(data) => maybe_escape_quote(f, data, false),
+        }
+    }
+}
+
+/// The extended header lines `git diff` writes before the `---` and `+++` lines
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GitMetadata<'a> {
+    /// The old path from the `diff --git` line, typically prefixed with `a/`
+    pub old_path: Cow<'a, str>,
+    /// The new path from the `diff --git` line, typically prefixed with `b/`
+    pub new_path: Cow<'a, str>,
+    /// Whether the non-ASCII characters of the paths on the `diff --git` line were escaped, like
+    /// [`File::escaped`]
+    pub escaped_paths: bool,
+    /// The abbreviated blob hash of the old file, from the `index` line
+    pub old_hash: Option<&'a str>,
+    /// The abbreviated blob hash of the new file, from the `index` line
+    pub new_hash: Option<&'a str>,
+    /// The file mode of the old file, e.g. `0o100644`, from the `old mode`, `deleted file mode`
+    /// or `index` line
+    pub old_mode: Option<u32>,
+    /// The file mode of the new file, e.g. `0o100755`, from the `new mode`, `new file mode` or
+    /// `index` line
+    pub new_mode: Option<u32>,
+    /// Whether the file was created, deleted, or modified
+    pub kind: FileChangeKind,
+    /// How similar the old and new files are as a percentage, from the `similarity index` line
+    /// of a rename or copy
+    pub similarity: Option<u8>,
+    /// How much of the file was rewritten as a percentage, from the `dissimilarity index` line
+    pub dissimilarity: Option<u8>,
+    /// An operation on the file itself, like a rename or copy
+    pub operation: Option<FileOperation<'a>>,
+}
+
+impl<'a> GitMetadata<'a> {
+    /// The old and new file modes if the mode of a kept file changed, e.g. after `chmod +x`
+    pub fn mode_change(&self) -> Option<(u32, u32)> {
+        match (self.kind, self.old_mode, self.new_mode) {
+            (FileChangeKind::Modified, Some(old), Some(new)) if old != new => Some((old, new)),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> fmt::Display for GitMetadata<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "diff --git ")?;
+        maybe_escape_quote(f, &self.old_path, self.escaped_paths)?;
+        write!(f, " ")?;
+        maybe_escape_quote(f, &self.new_path, self.escaped_paths)?;
+
+        // git only puts the mode on the index line when it didn't change
+        let index_mode = match (self.old_mode, self.new_mode) {
+            (Some(old), Some(new)) if old == new && self.old_hash.is_some() => Some(old),
+            _ => None,
+        };
+        match self.kind {
+            FileChangeKind::Created => {
+                if let Some(mode) = self.new_mode {
+                    write!(f, "\nnew file mode {:06o}", mode)?;
+                }
+            }
+            FileChangeKind::Deleted => {
+                if let Some(mode) = self.old_mode {
+                    write!(f, "\ndeleted file mode {:06o}", mode)?;
+                }
+            }
+            FileChangeKind::Modified if index_mode.is_none() => {
+                if let Some(mode) = self.old_mode {
+                    write!(f, "\nold mode {:06o}", mode)?;
+                }
+                if let Some(mode) = self.new_mode {
+                    write!(f, "\nnew mode {:06o}", mode)?;
+                }
+            }
+            FileChangeKind::Modified => {}
+        }
+        if let Some(similarity) = self.similarity {
+            write!(f, "\nsimilarity index {}%", similarity)?;
+        }
+        if let Some(dissimilarity) = self.dissimilarity {
+            write!(f, "\ndissimilarity index {}%", dissimilarity)?;
+        }
+        if let Some(operation) = &self.operation {
+            write!(f, "\n{}", operation)?;
+        }
+        if let (Some(old), Some(new)) = (self.old_hash, self.new_hash) {
+            write!(f, "\nindex {}..{}", old, new)?;
+            if let Some(mode) = index_mode {
+                write!(f, " {:06o}", mode)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether a patch creates, deletes, or modifies a file
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FileChangeKind {
+    /// The file is new, from a `new file mode` line
+    Created,
+    /// The file is removed, from a `deleted file mode` line
+    Deleted,
+    /// The file already exists and is kept
+    Modified,
+}
+
+/// The lines `svn diff` writes around a file's patch
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SvnMetadata<'a> {
+    /// The path from the `Index:` line before the patch
+    pub path: Cow<'a, str>,
+    /// The changes to versioned properties like `svn:eol-style`, from the `Property changes on:`
+    /// section after the patch's hunks
+    pub property_changes: Vec<PropertyChange<'a>>,
+}
+
+/// The lines `cvs diff` writes before a file's patch, about the revisions it compares
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CvsMetadata<'a> {
+    /// The path from the `Index:` line before the patch
+    pub path: Cow<'a, str>,
+    /// The path of the file in the repository, from the `RCS file:` line
+    pub rcs_file: &'a str,
+    /// The revisions from the `retrieving revision` lines, e.g. `1.2`. There is one for a diff
+    /// against the working copy and two for a diff between revisions.
+    pub revisions: Vec<&'a str>,
+    /// The `diff` command line that CVS ran, e.g. `diff -u -r1.2 -r1.3`
+    pub diff_command: Option<&'a str>,
+}
+
+impl<'a> fmt::Display for CvsMetadata<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Index: {}\n{}", self.path, "=".repeat(67))?;
+        write!(f, "\nRCS file: {}", self.rcs_file)?;
+        for revision in &self.revisions {
+            write!(f, "\nretrieving revision {}", revision)?;
+        }
+        if let Some(diff_command) = self.diff_command {
+            write!(f, "\n{}", diff_command)?;
+        }
+        Ok(())
+    }
+}
+
+/// The line `p4 diff` and `p4 describe` write before a file's hunks, like
+/// `==== //depot/main/foo.c#3 - /home/me/main/foo.c ====`
+///
+/// [`Patch::old`] and [`Patch::new`] have the same paths, without the revisions.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PerforceMetadata<'a> {
+    /// The depot path of the old file, e.g. `//depot/main/foo.c`
+    pub old_path: Cow<'a, str>,
+    /// The revision of the old file, e.g. `3`
+    pub old_revision: Option<&'a str>,
+    /// The Perforce file type of the old file, e.g. `text`, if given
+    pub old_file_type: Option<&'a str>,
+    /// The path of the new file: a local path for `p4 diff`, or `None` when the line only has
+    /// one path, like in the output of `p4 describe`
+    pub new_path: Option<Cow<'a, str>>,
+    /// The revision of the new file, if it is in the depot
+    pub new_revision: Option<&'a str>,
+    /// The Perforce file type of the new file, if given
+    pub new_file_type: Option<&'a str>,
+}
+
+impl<'a> fmt::Display for PerforceMetadata<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let file = |f: &mut fmt::Formatter, path, revision, file_type| {
+            write!(f, "{}", path)?;
+            if let Some(revision) = revision {
+                write!(f, "#{}", revision)?;
+            }
+            if let Some(file_type) = file_type {
+                write!(f, " ({})", file_type)?;
+            }
+            Ok(())
+        };
+        write!(f, "==== ")?;
+        file(f, &self.old_path, self.old_revision, self.old_file_type)?;
+        if let Some(new_path) = &self.new_path {
+            write!(f, " - ")?;
+            file(f, new_path, self.new_revision, self.new_file_type)?;
+        }
+        write!(f, " ====")
+    }
+}
+
+/// A change to a versioned property of a file, from the output of `svn diff`
+///
+/// The value is diffed like the file's content, in a hunk delimited by `##` instead of `@@`.
+/// Like GNU diff, Subversion follows the last line of a value without a newline with
+/// `\ No newline at end of property`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PropertyChange<'a> {
+    /// The name of the property, e.g. `svn:ignore`
+    pub name: &'a str,
+    /// Whether the property was added, modified or deleted
+    pub kind: PropertyChangeKind,
+    /// The old and new values of the property
+    pub hunk: Hunk<'a>,
+    /// true if the old value ends in a newline character
+    pub old_end_newline: bool,
+    /// true if the new value ends in a newline character
+    pub new_end_newline: bool,
+}
+
+impl<'a> PropertyChange<'a> {
+    fn fmt_lines(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        let kind = match self.kind {
+            PropertyChangeKind::Added => "Added",
+            PropertyChangeKind::Modified => "Modified",
+            PropertyChangeKind::Deleted => "Deleted",
+        };
+        write!(f, "{}: {}", kind, self.name)?;
+        let hunk = &self.hunk;
+        write!(f, "\n## -{} +{} ##", hunk.old_range, hunk.new_range)?;
+        let end_newlines = (self.old_end_newline, self.new_end_newline);
+        fmt_last_lines(f, &hunk.lines, end_newlines, "property")
+    }
+}
+
+impl<'a> fmt::Display for PropertyChange<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_lines(f)
+    }
+}
+
+/// The kind of a [`PropertyChange`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PropertyChangeKind {
+    /// The file didn't have the property before, written as `Added:`
+    Added,
+    /// The property's value changed, written as `Modified:`
+    Modified,
+    /// The file doesn't have the property anymore, written as `Deleted:`
+    Deleted,
+}
+
+/// An operation on a file other than changing its content, from git's extended header lines
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FileOperation<'a> {
+    /// The file was moved, from the `rename from` and `rename to` lines
+    ///
+    /// The paths are relative to the repository root, without any `a/` or `b/` prefix.
+    Renamed {
+        /// The path before the rename
+        from: Cow<'a, str>,
+        /// The path after the rename
+        to: Cow<'a, str>,
+        /// Whether the non-ASCII characters of the paths were escaped, like [`File::escaped`]
+        escaped: bool,
+    },
+    /// The file was copied, from the `copy from` and `copy to` lines
+    ///
+    /// The paths are relative to the repository root, without any `a/` or `b/` prefix.
+    Copied {
+        /// The path of the original file
+        from: Cow<'a, str>,
+        /// The path of the copy
+        to: Cow<'a, str>,
+        /// Whether the non-ASCII characters of the paths were escaped, like [`File::escaped`]
+        escaped: bool,
+    },
+}
+
+impl<'a> fmt::Display for FileOperation<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileOperation::Renamed { from, to, escaped } => {
+                write!(f, "rename from ")?;
+                maybe_escape_quote(f, from, *escaped)?;
+                write!(f, "\nrename to ")?;
+                maybe_escape_quote(f, to, *escaped)
+            }
+            FileOperation::Copied { from, to, escaped } => {
+                write!(f, "copy from ")?;
+                maybe_escape_quote(f, from, *escaped)?;
+                write!(f, "\ncopy to ")?;
+                maybe_escape_quote(f, to, *escaped)
+            }
+        }
+    }
+}
+
+/// A file in the output of a recursive `diff -r`, from [`Patch::from_recursive_diff`]
+// Most entries are patches, so boxing them wouldn't save memory
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DiffEntry<'a> {
+    /// The differences in a file
+    Patch(Patch<'a>),
+    /// A file or directory that only one side has, from a line like
+    /// `Only in new/docs: guide.txt`
+    OnlyIn {
+        /// The directory the file is in, including the side's top directory
+        dir: &'a str,
+        /// The name of the file or directory
+        name: &'a str,
+    },
+    /// Files that differ but weren't compared line by line, from a line like
+    /// `Files old/logo.svg and new/logo.svg differ`, which older versions of GNU diff write for
+    /// binary files and `diff -q` writes for all files
+    BinaryDiffer {
+        /// The path of the old file
+        old: Cow<'a, str>,
+        /// The path of the new file
+        new: Cow<'a, str>,
+        /// Whether the non-ASCII characters of the paths were escaped, like [`File::escaped`]
+        escaped: bool,
+    },
+}
+
+impl<'a> fmt::Display for DiffEntry<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiffEntry::Patch(patch) => write!(f, "{}", patch),
+            DiffEntry::OnlyIn { dir, name } => write!(f, "Only in {}: {}", dir, name),
+            DiffEntry::BinaryDiffer { old, new, escaped } => {
+                write!(f, "Files ")?;
+                maybe_escape_quote(f, old, *escaped)?;
+                write!(f, " and ")?;
+                maybe_escape_quote(f, new, *escaped)?;
+                write!(f, " differ")
+            }
+        }
+    }
+}
+
+/// The change to a binary file
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum BinaryPatch {
+    /// Only notes that the file changed, from a `Binary files ... differ` line
+    ///
+    /// The paths from that line are used for the patch's `old` and `new` files.
+    Changed,
+    /// A `GIT binary patch` from `git diff --binary`, containing the data needed to apply it
+    Git {
+        /// Turns the old file into the new file
+        forward: BinaryHunk,
+        /// Turns the new file back into the old file, if provided
+        reverse: Option<BinaryHunk>,
+    },
+}
+
+/// One half of a `GIT binary patch`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BinaryHunk {
+    /// Whether `data` is the complete file or a delta against the other file
+    pub kind: BinaryHunkKind,
+    /// The size of `data` after it is inflated
+    pub size: u64,
+    /// The zlib-deflated payload, already decoded from base85
+    pub data: Vec<u8>,
+}
+
+impl fmt::Display for BinaryHunk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            BinaryHunkKind::Literal => write!(f, "literal {}", self.size)?,
+            BinaryHunkKind::Delta => write!(f, "delta {}", self.size)?,
+        }
+        for chunk in self.data.chunks(base85::MAX_LINE_BYTES) {
+            write!(f, "\n{}", base85::encode_line(chunk))?;
         }
+        Ok(())
     }
 }
 
+/// The encoding of the data in a [`BinaryHunk`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum BinaryHunkKind {
+    /// The data is the complete contents of the resulting file
+    Literal,
+    /// The data is a git delta to apply to the other file
+    Delta,
+}
+
 /// One area where the files differ
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Hunk<'a> {
@@ -223,6 +1744,56 @@ pub struct Hunk<'a> {
 }
 
 impl<'a> Hunk<'a> {
+    /// Attempt to parse a single hunk, starting with its `@@` range line. This is useful for
+    /// fragments of a patch, like a hunk quoted in a code review comment.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{Hunk, Line};
+    /// let sample = "\
+    /// @@ -1,2 +1,2 @@ fn main()
+    /// -bacon
+    /// +python
+    ///  guido\n";
+    ///
+    /// let hunk = Hunk::parse(sample)?;
+    /// assert_eq!(hunk.hint(), Some("fn main()"));
+    /// assert_eq!(hunk.lines[1], Line::Add("python"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse(s: &'a str) -> Result<Self, ParseError<'a>> {
+        parse_hunk(s)
+    }
+
+    /// Like [`Hunk::parse`], but more forgiving of how the hunk was copied, e.g. into a code review
+    /// comment: empty lines before and after it are ignored, and so is a
+    /// `\ No newline at end of file` line.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{Hunk, Line};
+    /// let comment = "
+    /// @@ -1,1 +1,1 @@
+    /// -bacon
+    /// +python
+    /// \\ No newline at end of file
+    ///
+    /// ";
+    ///
+    /// let hunk = Hunk::from_fragment(comment)?;
+    /// assert_eq!(hunk.lines, [Line::Remove("bacon"), Line::Add("python")]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_fragment(s: &'a str) -> Result<Self, ParseError<'a>> {
+        parse_hunk_fragment(s)
+    }
+
     /// A nicer way to access the optional hint
     pub fn hint(&self) -> Option<&str> {
         let h = self.range_hint.trim_start();
@@ -232,6 +1803,116 @@ impl<'a> Hunk<'a> {
             Some(h)
         }
     }
+
+    /// Guess the function, type, or module this hunk modifies from its hint, e.g. `main` for
+    /// `@@ -1,2 +1,2 @@ fn main() {`. Useful for grouping hunks by what they change.
+    ///
+    /// This recognizes the definition keywords of common languages and C-style function
+    /// signatures, and returns `None` for anything else.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{Hunk, ScopeKind};
+    /// let hunk = Hunk::parse("@@ -1,1 +1,1 @@ impl<'a> Display for Patch<'a> {\n-x\n+y\n")?;
+    /// let scope = hunk.scope().unwrap();
+    /// assert_eq!(scope.kind, ScopeKind::Impl);
+    /// assert_eq!(scope.name, "Patch");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn scope(&self) -> Option<Scope<'a>> {
+        scope_of(self.range_hint)
+    }
+
+    /// Find added lines with trailing whitespace or indentation that mixes tabs and spaces, like
+    /// `git diff --check`. Removed and context lines aren't checked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{Hunk, WhitespaceErrorKind};
+    /// let hunk = Hunk::parse("@@ -1,1 +1,1 @@\n-bacon\n+python \n")?;
+    /// let errors = hunk.whitespace_errors();
+    /// assert_eq!(errors[0].line, 1);
+    /// assert_eq!(errors[0].kind, WhitespaceErrorKind::TrailingWhitespace);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn whitespace_errors(&self) -> Vec<WhitespaceError> {
+        whitespace_errors(self)
+    }
+
+    /// Whether some lines of this hunk end in `\r` and others don't, i.e. the file mixes CRLF
+    /// and LF line endings
+    ///
+    /// A `\r` at the end of a line is kept in its text, unless the patch itself uses CRLF line
+    /// endings. See [`Patch::strip_carriage_returns`] to remove them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{Hunk, Line};
+    /// let hunk = Hunk::parse("@@ -1,2 +1,2 @@\n-bacon\r\n+python\n guido\r\n")?;
+    /// assert_eq!(hunk.lines[0], Line::Remove("bacon\r"));
+    /// assert!(hunk.has_mixed_line_endings());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn has_mixed_line_endings(&self) -> bool {
+        let mut crlf = self.lines.iter().map(|line| match *line {
+            Line::Add(text) | Line::Remove(text) | Line::Context(text) => text.ends_with('\r'),
+        });
+        match crlf.next() {
+            Some(first) => crlf.any(|crlf| crlf != first),
+            None => false,
+        }
+    }
+
+    /// The text of the old file covered by this hunk, from its context and removed lines
+    ///
+    /// Every line ends with `\n`, including the last one. Whether the file really ends in a
+    /// newline is only known for the whole patch, see [`Patch::old_end_newline`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::Hunk;
+    /// let hunk = Hunk::parse("@@ -1,2 +1,2 @@\n-bacon\n+python\n guido\n")?;
+    /// assert_eq!(hunk.old_text(), "bacon\nguido\n");
+    /// assert_eq!(hunk.new_text(), "python\nguido\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn old_text(&self) -> String {
+        self.side_text(|line| match *line {
+            Line::Context(text) | Line::Remove(text) => Some(text),
+            Line::Add(_) => None,
+        })
+    }
+
+    /// The text of the new file covered by this hunk, from its context and added lines
+    ///
+    /// Like [`Hunk::old_text`], every line ends with `\n`.
+    pub fn new_text(&self) -> String {
+        self.side_text(|line| match *line {
+            Line::Context(text) | Line::Add(text) => Some(text),
+            Line::Remove(_) => None,
+        })
+    }
+
+    fn side_text(&self, side: impl Fn(&Line<'a>) -> Option<&'a str>) -> String {
+        let mut text = String::new();
+        for line in self.lines.iter().filter_map(side) {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text
+    }
 }
 
 impl<'a> fmt::Display for Hunk<'a> {
@@ -286,6 +1967,131 @@ impl<'a> fmt::Display for Line<'a> {
     }
 }
 
+/// A patch from a combined diff, which git shows for merge commits
+///
+/// Combined diffs compare the merge result against each of its parents at once, so hunks have one
+/// old range per parent and each line has one marker column per parent.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CombinedPatch<'a> {
+    /// The path from the `diff --cc` or `diff --combined` line
+    pub path: Cow<'a, str>,
+    /// Whether the non-ASCII characters of the path were escaped, like [`File::escaped`]
+    pub escaped_path: bool,
+    /// true for `diff --cc` (the default for `git show`), false for `diff --combined`
+    pub dense: bool,
+    /// The abbreviated object hash of each parent's file from the `index` line, if any
+    pub old_hashes: Vec<&'a str>,
+    /// The abbreviated object hash of the merged file from the `index` line, if any
+    pub new_hash: Option<&'a str>,
+    /// The file information of the parents' side of the diff, line prefix: `---`
+    pub old: File<'a>,
+    /// The file information of the merged side of the diff, line prefix: `+++`
+    pub new: File<'a>,
+    /// hunks of differences; each hunk shows one area where the merge differs from a parent
+    pub hunks: Vec<CombinedHunk<'a>>,
+    /// true if the last line of the file ends in a newline character
+    pub end_newline: bool,
+}
+
+impl<'a> CombinedPatch<'a> {
+    /// Attempt to parse as many combined patches as possible from the given string, e.g. the
+    /// output of `git show` for a merge commit. String must contain at least one patch.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::CombinedPatch;
+    /// let sample = "\
+    /// diff --cc f.txt
+    /// index f04eb26,ddc897f..571375f
+    /// --- a/f.txt
+    /// +++ b/f.txt
+    /// @@@ -1,3 -1,3 +1,3 @@@
+    ///   one
+    /// - 2
+    ///  -TWO
+    /// ++two-ish
+    ///   three\n";
+    ///
+    /// let patches = CombinedPatch::from_multiple(sample)?;
+    /// let hunk = &patches[0].hunks[0];
+    /// assert_eq!(hunk.old_ranges.len(), 2);
+    /// assert_eq!(hunk.lines[3].markers, "++");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_multiple(s: &'a str) -> Result<Vec<Self>, ParseError<'a>> {
+        parse_combined_patches(s)
+    }
+}
+
+impl<'a> fmt::Display for CombinedPatch<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "diff --{} ", if self.dense { "cc" } else { "combined" })?;
+        maybe_escape_quote(f, &self.path, self.escaped_path)?;
+        if let Some(new_hash) = self.new_hash {
+            write!(f, "\nindex {}..{}", self.old_hashes.join(","), new_hash)?;
+        }
+        write!(f, "\n--- {}", self.old)?;
+        write!(f, "\n+++ {}", self.new)?;
+        for hunk in &self.hunks {
+            write!(f, "\n{}", hunk)?;
+        }
+        if !self.end_newline {
+            write!(f, "\n\\ No newline at end of file")?;
+        }
+        Ok(())
+    }
+}
+
+/// One area where a merge differs from its parents, starting with an `@@@` range line
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CombinedHunk<'a> {
+    /// The range of lines in each parent's file, in the order of the merge's parents
+    pub old_ranges: Vec<Range>,
+    /// The range of lines in the merged file
+    pub new_range: Range,
+    /// Any trailing text after the hunk's range information
+    pub range_hint: &'a str,
+    /// Each line of text in the hunk, prefixed with one change marker per parent
+    pub lines: Vec<CombinedLine<'a>>,
+}
+
+impl<'a> fmt::Display for CombinedHunk<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // One more `@` than there are parents
+        let ats = "@".repeat(self.old_ranges.len() + 1);
+        write!(f, "{}", ats)?;
+        for range in &self.old_ranges {
+            write!(f, " -{}", range)?;
+        }
+        write!(f, " +{} {}{}", self.new_range, ats, self.range_hint)?;
+
+        for line in &self.lines {
+            write!(f, "\n{}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A line of a combined hunk
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CombinedLine<'a> {
+    /// One character per parent: `+` if the line is added relative to that parent, `-` if it is
+    /// removed relative to that parent, or a space if that parent has it unchanged
+    pub markers: &'a str,
+    /// The text of the line
+    pub text: &'a str,
+}
+
+impl<'a> fmt::Display for CombinedLine<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.markers, self.text)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -299,7 +2105,7 @@ mod tests {
             range_hint: "",
             lines: vec![],
         };
-        for (input, expected) in vec![
+        for (input, expected) in [
             ("", None),
             (" ", None),
             ("  ", None),
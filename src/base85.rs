@@ -0,0 +1,92 @@
+//! The base85 encoding git uses for the data in binary patches
+//!
+//! Each line starts with a character encoding the number of bytes on that line (`A`-`Z` for 1-26,
+//! `a`-`z` for 27-52), followed by groups of five characters that each encode four bytes.
+
+const ALPHABET: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+/// The maximum number of bytes git encodes on a single line
+pub(crate) const MAX_LINE_BYTES: usize = 52;
+
+fn digit(ch: u8) -> Option<u32> {
+    ALPHABET.iter().position(|&c| c == ch).map(|d| d as u32)
+}
+
+/// Decode a single line, returning `None` if it isn't valid base85 with a matching length
+pub(crate) fn decode_line(line: &str) -> Option<Vec<u8>> {
+    let (&len, data) = line.as_bytes().split_first()?;
+    let len = match len {
+        b'A'..=b'Z' => len - b'A' + 1,
+        b'a'..=b'z' => len - b'a' + 27,
+        _ => return None,
+    } as usize;
+    if data.len() != len.div_ceil(4) * 5 {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(data.len() / 5 * 4);
+    for group in data.chunks(5) {
+        let mut acc: u32 = 0;
+        for &ch in group {
+            acc = acc.checked_mul(85)?.checked_add(digit(ch)?)?;
+        }
+        out.extend_from_slice(&acc.to_be_bytes());
+    }
+    out.truncate(len);
+    Some(out)
+}
+
+/// Encode at most [`MAX_LINE_BYTES`] bytes as a single line
+pub(crate) fn encode_line(data: &[u8]) -> String {
+    debug_assert!(!data.is_empty() && data.len() <= MAX_LINE_BYTES);
+    let mut line = String::with_capacity(1 + data.len().div_ceil(4) * 5);
+    line.push(match data.len() {
+        len @ 1..=26 => (b'A' + len as u8 - 1) as char,
+        len => (b'a' + len as u8 - 27) as char,
+    });
+    for group in data.chunks(4) {
+        let mut bytes = [0; 4];
+        bytes[..group.len()].copy_from_slice(group);
+        let mut acc = u32::from_be_bytes(bytes);
+        let mut chars = [0; 5];
+        for ch in chars.iter_mut().rev() {
+            *ch = ALPHABET[(acc % 85) as usize];
+            acc /= 85;
+        }
+        line.extend(chars.iter().map(|&ch| ch as char));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_decode_line() {
+        assert_eq!(
+            decode_line("JcmZQz1ONa700IC2"),
+            Some(vec![120, 1, 99, 96, 4, 0, 0, 3, 0, 2])
+        );
+        assert_eq!(
+            decode_line("HcmV?d00001"),
+            Some(vec![120, 1, 3, 0, 0, 0, 0, 1])
+        );
+        // length doesn't match the data
+        assert_eq!(decode_line("IcmV?d00001"), None);
+        // not base85
+        assert_eq!(decode_line("Hcm\"?d00001"), None);
+        assert_eq!(decode_line(""), None);
+    }
+
+    #[test]
+    fn test_encode_line() {
+        for line in ["JcmZQz1ONa700IC2", "HcmV?d00001", "NcmYdHN@ieW0ssZZ0V)6h"] {
+            assert_eq!(encode_line(&decode_line(line).unwrap()), line);
+        }
+        let data: Vec<u8> = (0..MAX_LINE_BYTES as u8).collect();
+        assert_eq!(decode_line(&encode_line(&data)), Some(data));
+    }
+}
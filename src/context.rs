@@ -0,0 +1,74 @@
+use crate::ast::*;
+
+// Builds the output of `Patch::to_context_format`, in the format GNU `diff -c` writes
+pub(crate) fn context_format(patch: &Patch<'_>) -> String {
+    let mut out = vec![format!("*** {}", patch.old), format!("--- {}", patch.new)];
+    for (i, hunk) in patch.hunks.iter().enumerate() {
+        let last = i + 1 == patch.hunks.len();
+        let (old, new) = context_sides(hunk);
+
+        out.push(format!("***************{}", hunk.range_hint));
+        out.push(format!("*** {} ****", context_range(&hunk.old_range)));
+        // A side without changes is left out, since its lines are all in the other side
+        if old.iter().any(|line| !line.starts_with(' ')) {
+            out.extend(old);
+            if last && !patch.old_end_newline {
+                out.push("\\ No newline at end of file".to_owned());
+            }
+        }
+        out.push(format!("--- {} ----", context_range(&hunk.new_range)));
+        if new.iter().any(|line| !line.starts_with(' ')) {
+            out.extend(new);
+            if last && !patch.new_end_newline {
+                out.push("\\ No newline at end of file".to_owned());
+            }
+        }
+    }
+    out.join(patch.line_ending.as_str())
+}
+
+// The old and new lines of a hunk. Lines of a change that both removes and adds lines are marked
+// with `!`, other removed and added lines with `-` and `+`.
+fn context_sides(hunk: &Hunk<'_>) -> (Vec<String>, Vec<String>) {
+    let (mut old, mut new) = (Vec::new(), Vec::new());
+    let is_context = |line: &Line<'_>| matches!(line, Line::Context(_));
+    let mut i = 0;
+    while i < hunk.lines.len() {
+        if let Line::Context(text) = hunk.lines[i] {
+            old.push(format!("  {}", text));
+            new.push(format!("  {}", text));
+            i += 1;
+            continue;
+        }
+        let end = hunk.lines[i..]
+            .iter()
+            .position(is_context)
+            .map_or(hunk.lines.len(), |len| i + len);
+        let change = &hunk.lines[i..end];
+        let removes = change.iter().any(|line| matches!(line, Line::Remove(_)));
+        let adds = change.iter().any(|line| matches!(line, Line::Add(_)));
+        for line in change {
+            match *line {
+                Line::Remove(text) => {
+                    old.push(format!("{} {}", if adds { '!' } else { '-' }, text))
+                }
+                Line::Add(text) => {
+                    new.push(format!("{} {}", if removes { '!' } else { '+' }, text))
+                }
+                Line::Context(_) => unreachable!(),
+            }
+        }
+        i = end;
+    }
+    (old, new)
+}
+
+// GNU diff writes the first and last line of a range, or only one number for a range of one line.
+// For an empty range, that is the line before it, like the start of a unified range.
+fn context_range(range: &Range) -> String {
+    if range.count <= 1 {
+        range.start.to_string()
+    } else {
+        format!("{},{}", range.start, range.start + range.count - 1)
+    }
+}
@@ -0,0 +1,139 @@
+use std::borrow::Cow;
+
+/// The DEP-3 fields that describe a Debian patch, like `Description:` and `Forwarded:`, from
+/// [`Patch::dep3_headers`](crate::Patch::dep3_headers)
+///
+/// See <https://dep-team.pages.debian.net/deps/dep3/> for the fields and what they mean.
+///
+/// # Example
+///
+/// ```
+/// # use patch::Dep3Headers;
+/// let headers = Dep3Headers::parse("\
+/// Description: Fix the greeting
+///  The old greeting was confusing.
+///  .
+///  It was also misspelled.
+/// Origin: upstream, https://example.com/commit/961f106
+/// Bug-Debian: https://bugs.debian.org/123456
+/// Forwarded: not-needed
+/// ");
+///
+/// assert_eq!(
+///     headers.description(),
+///     Some("Fix the greeting\nThe old greeting was confusing.\n\nIt was also misspelled."),
+/// );
+/// assert_eq!(headers.get("forwarded"), Some("not-needed"));
+/// assert_eq!(headers.bugs(), [("Debian", "https://bugs.debian.org/123456")]);
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Dep3Headers<'a> {
+    /// The fields in the order they are written
+    pub fields: Vec<Dep3Field<'a>>,
+}
+
+/// A field of [`Dep3Headers`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Dep3Field<'a> {
+    /// The name of the field, e.g. `Bug-Debian`
+    pub name: &'a str,
+    /// The value of the field, with its continuation lines on separate lines. Their leading
+    /// space is removed and a line of only `.` is an empty line.
+    pub value: Cow<'a, str>,
+}
+
+impl<'a> Dep3Headers<'a> {
+    /// Read the DEP-3 fields of the text before a patch
+    ///
+    /// Reading stops at a line of `---`, which separates the headers from the patch in some
+    /// patches. Lines that aren't fields or their continuation, like free-form text between the
+    /// fields, are skipped.
+    pub fn parse(s: &'a str) -> Self {
+        let mut fields: Vec<Dep3Field<'a>> = Vec::new();
+        let mut continues = false;
+        for line in s.lines() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if line == "---" {
+                break;
+            }
+            if let Some(more) = line.strip_prefix([' ', '\t']) {
+                if let Some(field) = fields.last_mut().filter(|_| continues) {
+                    let more = more.trim_end();
+                    let value = field.value.to_mut();
+                    value.push('\n');
+                    value.push_str(if more == "." { "" } else { more });
+                }
+                continue;
+            }
+            let field = line.split_once(':').filter(|(name, value)| {
+                !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+                    && (value.is_empty() || value.starts_with([' ', '\t']))
+            });
+            continues = field.is_some();
+            if let Some((name, value)) = field {
+                fields.push(Dep3Field {
+                    name,
+                    value: Cow::Borrowed(value.trim()),
+                });
+            }
+        }
+        Dep3Headers { fields }
+    }
+
+    /// The value of the first field with the given name, which is compared ignoring case
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|field| field.name.eq_ignore_ascii_case(name))
+            .map(|field| field.value.as_ref())
+    }
+
+    /// The `Description` field, or `Subject` which can be used instead. Its first line is a
+    /// summary of the patch.
+    pub fn description(&self) -> Option<&str> {
+        self.get("Description").or_else(|| self.get("Subject"))
+    }
+
+    /// The `Origin` field: where the patch comes from, e.g. `upstream, <url>`
+    pub fn origin(&self) -> Option<&str> {
+        self.get("Origin")
+    }
+
+    /// The `Forwarded` field: whether the patch was sent upstream, e.g. `not-needed` or a URL
+    pub fn forwarded(&self) -> Option<&str> {
+        self.get("Forwarded")
+    }
+
+    /// The `Author` field, or `From` which can be used instead
+    pub fn author(&self) -> Option<&str> {
+        self.get("Author").or_else(|| self.get("From"))
+    }
+
+    /// The `Last-Update` field, the date the patch was last changed as `YYYY-MM-DD`
+    pub fn last_update(&self) -> Option<&str> {
+        self.get("Last-Update")
+    }
+
+    /// The vendor and URL of each `Bug` and `Bug-<Vendor>` field, where the vendor is empty for
+    /// the upstream bug tracker's `Bug` field
+    pub fn bugs(&self) -> Vec<(&str, &str)> {
+        self.fields
+            .iter()
+            .filter_map(|field| {
+                let vendor = if field.name.eq_ignore_ascii_case("Bug") {
+                    ""
+                } else {
+                    let prefix = field.name.get(..4)?;
+                    if !prefix.eq_ignore_ascii_case("Bug-") {
+                        return None;
+                    }
+                    &field.name[4..]
+                };
+                Some((vendor, field.value.as_ref()))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,150 @@
+use crate::ast::*;
+use crate::scope::ScopeKind;
+
+// Builds the summary returned by `Patch::describe`, e.g.
+// `modifies src/lib.rs: replaces 3 lines with 2 in function parse, adds 10 lines at line 80`
+pub(crate) fn describe(patch: &Patch<'_>) -> String {
+    let path = if patch.is_deletion() {
+        &patch.old.path
+    } else {
+        &patch.new.path
+    };
+    let binary = if patch.binary.is_some() {
+        "binary file "
+    } else {
+        ""
+    };
+
+    let operation = patch.git.as_ref().and_then(|git| git.operation.as_ref());
+    let mut summary = match (patch.kind(), operation) {
+        (FileChangeKind::Created, _) => format!("creates {}{}", binary, path),
+        (FileChangeKind::Deleted, _) => return format!("deletes {}{}", binary, path),
+        (_, Some(FileOperation::Renamed { from, to, .. })) => format!("renames {} to {}", from, to),
+        (_, Some(FileOperation::Copied { from, to, .. })) => format!("copies {} to {}", from, to),
+        _ => format!("modifies {}{}", binary, path),
+    };
+    if let Some((old, new)) = patch.git.as_ref().and_then(GitMetadata::mode_change) {
+        summary += &format!(", changes its mode from {:06o} to {:06o}", old, new);
+    }
+
+    let hunks: Vec<String> = patch.hunks.iter().filter_map(describe_hunk).collect();
+    if patch.is_creation() {
+        let added: usize = patch.hunks.iter().map(|hunk| hunk.lines.len()).sum();
+        if added > 0 {
+            summary += &format!(" with {}", lines(added));
+        }
+    } else if !hunks.is_empty() {
+        summary += ": ";
+        summary += &hunks.join(", ");
+    }
+    summary
+}
+
+fn describe_hunk(hunk: &Hunk<'_>) -> Option<String> {
+    let (mut added, mut removed) = (0, 0);
+    for line in &hunk.lines {
+        match line {
+            Line::Add(_) => added += 1,
+            Line::Remove(_) => removed += 1,
+            Line::Context(_) => {}
+        }
+    }
+    let change = match (removed, added) {
+        (0, 0) => return None,
+        (0, added) => format!("adds {}", lines(added)),
+        (removed, 0) => format!("removes {}", lines(removed)),
+        (removed, added) if removed == added => format!("replaces {}", lines(removed)),
+        (removed, added) => format!("replaces {} with {}", lines(removed), added),
+    };
+
+    let location = match hunk.scope() {
+        Some(scope) => {
+            let kind = match scope.kind {
+                ScopeKind::Function => "function",
+                ScopeKind::Type => "type",
+                ScopeKind::Impl => "impl",
+                ScopeKind::Module => "module",
+            };
+            format!("in {} {}", kind, scope.name)
+        }
+        None => {
+            // The line in the old file where the first change is
+            let context = hunk
+                .lines
+                .iter()
+                .take_while(|line| matches!(line, Line::Context(_)))
+                .count() as u64;
+            format!("at line {}", (hunk.old_range.start + context).max(1))
+        }
+    };
+    Some(format!("{} {}", change, location))
+}
+
+fn lines(count: usize) -> String {
+    if count == 1 {
+        "1 line".to_string()
+    } else {
+        format!("{} lines", count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_describe() -> Result<(), crate::ParseError<'static>> {
+        let sample = "\
+--- src/lib.rs
++++ src/lib.rs
+@@ -10,4 +10,2 @@ fn bar() {
+ let a = 1;
+-let b = 2;
+-let c = 3;
+-let d = 4;
++let b = 5;
+@@ -80,1 +78,3 @@
+ }
++
++// The end
+";
+        let patch = Patch::from_single(sample)?;
+        assert_eq!(
+            describe(&patch),
+            "modifies src/lib.rs: replaces 3 lines with 1 in function bar, \
+            adds 2 lines at line 81"
+        );
+
+        let sample = "\
+diff --git a/old.rs b/new.rs
+similarity index 100%
+rename from old.rs
+rename to new.rs
+diff --git a/tests/baz.rs b/tests/baz.rs
+new file mode 100644
+--- /dev/null
++++ b/tests/baz.rs
+@@ -0,0 +1,2 @@
++#[test]
++fn baz() {}
+diff --git a/run.sh b/run.sh
+deleted file mode 100755
+--- a/run.sh
++++ /dev/null
+@@ -1,1 +0,0 @@
+-echo hi
+";
+        let patches = Patch::from_multiple(sample)?;
+        let described: Vec<String> = patches.iter().map(describe).collect();
+        assert_eq!(
+            described,
+            [
+                "renames old.rs to new.rs",
+                "creates b/tests/baz.rs with 2 lines",
+                "deletes a/run.sh",
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use crate::parser::{parse_ed_script, ParseError};
+
+/// An ed script from `diff -e`, which lists the commands that turn the old file into the new one
+///
+/// Unlike a unified diff, an ed script has no file names or context lines, and doesn't contain
+/// the text of the lines it deletes, so it can't be turned into [`Hunk`](crate::Hunk)s without
+/// the old file. `diff -e` writes the commands from the end of the file to the start, so that
+/// each command's line numbers are still valid when it runs.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct EdScript<'a> {
+    /// The commands, in the order they run
+    pub commands: Vec<EdCommand<'a>>,
+}
+
+impl<'a> EdScript<'a> {
+    /// Attempt to parse an ed script, like the output of `diff -e`. An empty string is an empty
+    /// script, for files without differences.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{EdCommandKind, EdScript};
+    /// let sample = "\
+    /// 3,4d
+    /// 1c
+    /// python
+    /// .\n";
+    ///
+    /// let script = EdScript::parse(sample)?;
+    /// assert_eq!(script.commands[0].kind, EdCommandKind::Delete);
+    /// assert_eq!((script.commands[0].start, script.commands[0].end), (3, 4));
+    /// assert_eq!(script.commands[1].lines, ["python"]);
+    /// assert_eq!(format!("{}\n", script), sample);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse(s: &'a str) -> Result<Self, ParseError<'a>> {
+        parse_ed_script(s)
+    }
+}
+
+impl<'a> fmt::Display for EdScript<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, command) in self.commands.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", command)?;
+        }
+        Ok(())
+    }
+}
+
+/// A command of an [`EdScript`], which changes one range of lines
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EdCommand<'a> {
+    /// The first line of the old file that the command changes or deletes, or the line to append
+    /// after, where 0 is the start of the file
+    pub start: u64,
+    /// The last line of the old file that the command changes or deletes. This is the same as
+    /// `start` for commands on a single line and for appends.
+    pub end: u64,
+    /// What the command does with the lines
+    pub kind: EdCommandKind,
+    /// The new text of an append or change command, without line endings. This is empty for
+    /// deletes.
+    pub lines: Vec<&'a str>,
+}
+
+impl<'a> fmt::Display for EdCommand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.start)?;
+        if self.end != self.start {
+            write!(f, ",{}", self.end)?;
+        }
+        let kind = match self.kind {
+            EdCommandKind::Append => 'a',
+            EdCommandKind::Change => 'c',
+            EdCommandKind::Delete => return write!(f, "d"),
+        };
+        write!(f, "{}", kind)?;
+        for (i, line) in self.lines.iter().enumerate() {
+            if *line == "." {
+                // A line of only `.` would end the text, so write it like GNU diff does: as `..`,
+                // which is fixed up after the text ends, then continue appending the rest
+                write!(f, "\n..\n.\ns/.//")?;
+                if i + 1 == self.lines.len() {
+                    return Ok(());
+                }
+                write!(f, "\na")?;
+            } else {
+                write!(f, "\n{}", line)?;
+            }
+        }
+        write!(f, "\n.")
+    }
+}
+
+/// What an [`EdCommand`] does
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EdCommandKind {
+    /// Add lines after a line, written as `a`
+    Append,
+    /// Replace a range of lines, written as `c`
+    Change,
+    /// Delete a range of lines, written as `d`
+    Delete,
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use crate::ast::{Patch, Span};
+use crate::options::ParserOptions;
+use crate::parser::{parse_multiple_patches_with_spans, ParseError};
+
+/// Edits single files' patches inside a larger patch file, leaving the rest of it untouched
+///
+/// `Display` writes the original text with only the byte ranges of the replaced and removed
+/// patches changed, so a huge patch file keeps its formatting and any text between the patches.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), patch::ParseError<'static>> {
+/// # use patch::{Line, PatchFileEditor};
+/// let sample = "\
+/// Fixes for the vendored copy
+/// --- a/one.txt
+/// +++ b/one.txt
+/// @@ -1,1 +1,1 @@
+/// -one
+/// +ONE
+/// --- a/two.txt
+/// +++ b/two.txt
+/// @@ -1,1 +1,1 @@
+/// -two
+/// +TWO\n";
+///
+/// let mut editor = PatchFileEditor::new(sample)?;
+/// let index = editor.position("b/two.txt").unwrap();
+/// let mut patch = editor.patches()[index].clone();
+/// patch.hunks[0].lines[1] = Line::Add("Two");
+/// editor.replace(index, patch);
+/// editor.remove(0);
+/// assert_eq!(
+///     editor.to_string(),
+///     "Fixes for the vendored copy\n--- a/two.txt\n+++ b/two.txt\n@@ -1,1 +1,1 @@\n-two\n+Two\n",
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PatchFileEditor<'a> {
+    source: &'a str,
+    patches: Vec<Patch<'a>>,
+    spans: Vec<Span>,
+    edits: Vec<Edit>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Edit {
+    Unchanged,
+    Replaced,
+    Removed,
+}
+
+impl<'a> PatchFileEditor<'a> {
+    /// Parse a patch file for editing. It must contain at least one patch.
+    pub fn new(s: &'a str) -> Result<Self, ParseError<'a>> {
+        PatchFileEditor::with_options(s, &ParserOptions::default())
+    }
+
+    /// Like [`PatchFileEditor::new`], but parsing with the given [`ParserOptions`] instead of the
+    /// default ones
+    pub fn with_options(s: &'a str, options: &ParserOptions) -> Result<Self, ParseError<'a>> {
+        let (patches, spans): (Vec<_>, Vec<_>) = parse_multiple_patches_with_spans(s, options)?
+            .into_iter()
+            .unzip();
+        let edits = vec![Edit::Unchanged; patches.len()];
+        Ok(PatchFileEditor {
+            source: s,
+            patches,
+            spans,
+            edits,
+        })
+    }
+
+    /// The patches in the file, including any replacements. Removed patches keep their index.
+    pub fn patches(&self) -> &[Patch<'a>] {
+        &self.patches
+    }
+
+    /// The index of the first patch that isn't removed and changes the given path, on either
+    /// side
+    pub fn position(&self, path: &str) -> Option<usize> {
+        (0..self.patches.len()).find(|&i| {
+            let patch = &self.patches[i];
+            self.edits[i] != Edit::Removed && (patch.old.path == path || patch.new.path == path)
+        })
+    }
+
+    /// Replace the patch at `index` with another one
+    ///
+    /// Only the patch itself is replaced: the text before it in the file, like its
+    /// [`Patch::preamble`], stays as it is, so the preamble and byte order mark of `patch` are
+    /// ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn replace(&mut self, index: usize, patch: Patch<'a>) {
+        self.patches[index] = patch;
+        self.edits[index] = Edit::Replaced;
+    }
+
+    /// Remove the patch at `index` from the file. The text before it in the file, like its
+    /// [`Patch::preamble`], stays as it is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) {
+        self.edits[index] = Edit::Removed;
+    }
+}
+
+impl<'a> fmt::Display for PatchFileEditor<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut offset = 0;
+        for ((patch, span), edit) in self.patches.iter().zip(&self.spans).zip(&self.edits) {
+            write!(f, "{}", &self.source[offset..span.start])?;
+            let original = &self.source[span.start..span.end];
+            match edit {
+                Edit::Unchanged => write!(f, "{}", original)?,
+                Edit::Replaced => {
+                    let patch = Patch {
+                        byte_order_mark: false,
+                        preamble: None,
+                        ..patch.clone()
+                    };
+                    write!(f, "{}", patch)?;
+                    // The patch's span includes the line ending of its last line
+                    if original.ends_with('\n') {
+                        write!(f, "{}", patch.line_ending.as_str())?;
+                    }
+                }
+                Edit::Removed => {}
+            }
+            offset = span.end;
+        }
+        write!(f, "{}", &self.source[offset..])
+    }
+}
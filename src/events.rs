@@ -0,0 +1,83 @@
+use crate::ast::{File, GitMetadata, Line};
+use crate::parser::{EventParser, ParseError};
+use crate::scan::HunkHeader;
+
+/// The parts of a patch file as they are read, from [`PatchEvents`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PatchEvent<'a> {
+    /// The headers of the next file's patch
+    FileHeader(Box<FileHeaderEvent<'a>>),
+    /// The `@@` header line of the next hunk
+    HunkHeader(HunkHeader<'a>),
+    /// A line of the current hunk
+    Line(Line<'a>),
+    /// The end of the current file's patch
+    EndOfFile,
+}
+
+/// The headers of a file's patch, from [`PatchEvent::FileHeader`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FileHeaderEvent<'a> {
+    /// The file information of the `-` side of the diff, line prefix: `---`
+    pub old: File<'a>,
+    /// The file information of the `+` side of the diff, line prefix: `+++`
+    pub new: File<'a>,
+    /// The extended header information provided by `git diff`, if any
+    pub git: Option<GitMetadata<'a>>,
+}
+
+/// Reads a patch file as a sequence of [`PatchEvent`]s, without building a [`Patch`] for each
+/// file or collecting the lines of each hunk
+///
+/// The input is checked like [`Patch::from_multiple`] checks it, and the iterator ends after the
+/// first error. Preambles and `\ No newline at end of file` lines are skipped.
+///
+/// # Example
+///
+/// ```
+/// # use patch::{Line, PatchEvent, PatchEvents};
+/// let sample = "\
+/// --- a/one.txt
+/// +++ b/one.txt
+/// @@ -1,2 +1,2 @@
+///  one
+/// -two
+/// +TWO
+/// --- a/two.txt
+/// +++ b/two.txt
+/// @@ -1,1 +1,1 @@
+/// -three
+/// +THREE\n";
+///
+/// let mut added = 0;
+/// for event in PatchEvents::new(sample) {
+///     if let PatchEvent::Line(Line::Add(_)) = event.unwrap() {
+///         added += 1;
+///     }
+/// }
+/// assert_eq!(added, 2);
+/// ```
+///
+/// [`Patch`]: crate::Patch
+/// [`Patch::from_multiple`]: crate::Patch::from_multiple
+#[derive(Debug, Clone)]
+pub struct PatchEvents<'a> {
+    parser: EventParser<'a>,
+}
+
+impl<'a> PatchEvents<'a> {
+    /// Start reading a patch file
+    pub fn new(s: &'a str) -> Self {
+        PatchEvents {
+            parser: EventParser::new(s),
+        }
+    }
+}
+
+impl<'a> Iterator for PatchEvents<'a> {
+    type Item = Result<PatchEvent<'a>, ParseError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parser.next_event()
+    }
+}
@@ -0,0 +1,52 @@
+//! Checks for fuzz targets, enabled by the `fuzzing` feature
+//!
+//! Each check panics when it finds a bug, so a fuzzer reports the input that triggered it.
+
+use crate::ast::Patch;
+use crate::options::ParserOptions;
+use crate::warning::WarningKind;
+
+/// Check that input accepted with counted hunks parses to the same patches without counting
+/// them, so the two ways of finding the end of a hunk don't drift apart
+///
+/// This is checked for both [`ParserOptions::gnu`] and [`ParserOptions::git`]. Input that fails
+/// to parse with counted hunks is ignored, and so is input with lines past the ranges of a hunk,
+/// which are part of the hunk without counting.
+///
+/// # Panics
+///
+/// If the input fails to parse without counting, or parses to different patches.
+///
+/// # Example
+///
+/// ```
+/// let sample = "\
+/// --- before.py
+/// +++ after.py
+/// @@ -1,1 +1,1 @@
+/// -bacon
+/// +python\n";
+///
+/// patch::fuzzing::assert_lenient_agrees(sample);
+/// ```
+pub fn assert_lenient_agrees(input: &str) {
+    for strict in [ParserOptions::gnu(), ParserOptions::git()] {
+        let (patches, warnings) = match Patch::from_multiple_with_warnings(input, &strict) {
+            Ok(parsed) => parsed,
+            Err(_) => continue,
+        };
+        if warnings
+            .iter()
+            .any(|warning| warning.kind == WarningKind::ExtraHunkLines)
+        {
+            continue;
+        }
+        let lenient = strict.strict_counts(false);
+        let lenient_patches = Patch::from_multiple_with_options(input, &lenient)
+            .unwrap_or_else(|err| panic!("input fails without counting, error: {}", err));
+        assert_eq!(
+            lenient_patches, patches,
+            "input parses differently without counting"
+        );
+    }
+}
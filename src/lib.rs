@@ -45,8 +45,47 @@
 
 #![deny(unused_must_use)]
 
+mod ansi;
 mod ast;
+mod base85;
+mod context;
+mod dep3;
+mod describe;
+mod ed;
+mod editor;
+mod events;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+mod mail;
+mod options;
 mod parser;
+mod range_diff;
+mod roundtrip;
+mod scan;
+mod scope;
+mod series;
+mod stats;
+mod warning;
+mod whitespace;
+mod word_diff;
 
+pub use ansi::strip_ansi_colors;
 pub use ast::*;
+pub use dep3::{Dep3Field, Dep3Headers};
+pub use ed::{EdCommand, EdCommandKind, EdScript};
+pub use editor::PatchFileEditor;
+pub use events::{FileHeaderEvent, PatchEvent, PatchEvents};
+pub use mail::CommitPatch;
+pub use options::{EscapeSet, LimitExceeded, ParserOptions};
 pub use parser::ParseError;
+pub use range_diff::{
+    RangeDiff, RangeDiffCommit, RangeDiffEntry, RangeDiffSection, RangeDiffStatus,
+};
+pub use roundtrip::{verify_roundtrip, RoundtripReport};
+pub use scan::{HunkHeader, PatchHeaders};
+pub use scope::{Scope, ScopeKind};
+pub use series::{PatchSeries, Series, SeriesEntry, SeriesPatch};
+pub use stats::FileStat;
+pub use warning::{Warning, WarningKind};
+pub use whitespace::{visualize_whitespace, WhitespaceError, WhitespaceErrorKind};
+pub use word_diff::{WordDiffHunk, WordDiffLine, WordDiffPatch, WordDiffSpan};
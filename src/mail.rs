@@ -0,0 +1,177 @@
+use std::borrow::Cow;
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::ast::Patch;
+use crate::parser::{parse_commit_patch, parse_mbox, ParseError};
+
+/// A commit as an email written by `git format-patch`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CommitPatch<'a> {
+    /// The `From` header, e.g. `phil <uniphil@gmail.com>`
+    ///
+    /// MIME encoded words (`=?UTF-8?q?...?=`) are left as they are.
+    pub author: Cow<'a, str>,
+    /// The `Date` header
+    pub date: DateTime<FixedOffset>,
+    /// The `Subject` header, without prefixes in brackets like `[PATCH v2 1/3]`
+    pub subject: Cow<'a, str>,
+    /// The rest of the commit message, between the headers and the `---` line
+    pub message: &'a str,
+    /// The patches after the diffstat. This is empty for a cover letter.
+    pub patches: Vec<Patch<'a>>,
+}
+
+impl<'a> CommitPatch<'a> {
+    /// Attempt to parse a single email written by `git format-patch`
+    ///
+    /// The `From <sha>` line that starts each email in the mbox format, the diffstat, and the
+    /// signature with git's version at the end are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::CommitPatch;
+    /// let sample = "\
+    /// From 961f106701c472f0f3c95609c0b0bf99fb378d98 Mon Sep 17 00:00:00 2001
+    /// From: phil <uniphil@gmail.com>
+    /// Date: Sat, 16 Oct 2021 12:00:00 -0400
+    /// Subject: [PATCH] Fix the greeting
+    ///
+    /// The old greeting was confusing.
+    /// ---
+    ///  hello.txt | 2 +-
+    ///  1 file changed, 1 insertion(+), 1 deletion(-)
+    ///
+    /// diff --git a/hello.txt b/hello.txt
+    /// index ce01362..dd7e1c6 100644
+    /// --- a/hello.txt
+    /// +++ b/hello.txt
+    /// @@ -1 +1 @@
+    /// -hello
+    /// +goodbye
+    /// -- \n\
+    /// 2.39.5
+    /// ";
+    ///
+    /// let commit = CommitPatch::parse(sample)?;
+    /// assert_eq!(commit.author, "phil <uniphil@gmail.com>");
+    /// assert_eq!(commit.subject, "Fix the greeting");
+    /// assert_eq!(commit.message, "The old greeting was confusing.");
+    /// assert_eq!(commit.patches.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse(s: &'a str) -> Result<Self, ParseError<'a>> {
+        parse_commit_patch(s)
+    }
+
+    /// Attempt to parse every email in an mbox file, like the output of
+    /// `git format-patch --stdout` for a patch series. The emails are returned in order, and the
+    /// mbox must contain at least one.
+    ///
+    /// Each email starts with a `From <sha> <date>` line and is parsed like
+    /// [`CommitPatch::parse`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::CommitPatch;
+    /// let sample = "\
+    /// From 961f106701c472f0f3c95609c0b0bf99fb378d98 Mon Sep 17 00:00:00 2001
+    /// From: phil <uniphil@gmail.com>
+    /// Date: Sat, 16 Oct 2021 12:00:00 -0400
+    /// Subject: [PATCH 1/2] Fix the greeting
+    ///
+    /// ---
+    /// diff --git a/hello.txt b/hello.txt
+    /// --- a/hello.txt
+    /// +++ b/hello.txt
+    /// @@ -1 +1 @@
+    /// -hello
+    /// +goodbye
+    /// -- \n\
+    /// 2.39.5
+    ///
+    /// From 0bc302b2c6b79b3e5a7b1e2d03c8aa5a2e3f2d1c Mon Sep 17 00:00:00 2001
+    /// From: phil <uniphil@gmail.com>
+    /// Date: Sat, 16 Oct 2021 12:05:00 -0400
+    /// Subject: [PATCH 2/2] Add a name
+    ///
+    /// ---
+    /// diff --git a/hello.txt b/hello.txt
+    /// --- a/hello.txt
+    /// +++ b/hello.txt
+    /// @@ -1 +1,2 @@
+    ///  goodbye
+    /// +phil
+    /// -- \n\
+    /// 2.39.5
+    /// ";
+    ///
+    /// let series = CommitPatch::from_mbox(sample)?;
+    /// assert_eq!(series.len(), 2);
+    /// assert_eq!(series[1].subject, "Add a name");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_mbox(s: &'a str) -> Result<Vec<Self>, ParseError<'a>> {
+        parse_mbox(s)
+    }
+    /// The trailers at the end of the commit message, like `Signed-off-by: phil <...>`, as
+    /// pairs of keys and values
+    ///
+    /// Like git, this only looks at the last paragraph of the message, and only if every line of
+    /// it is a trailer. Lines starting with whitespace continue the previous trailer and are left
+    /// out of its value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::CommitPatch;
+    /// let sample = "\
+    /// From: phil <uniphil@gmail.com>
+    /// Date: Sat, 16 Oct 2021 12:00:00 -0400
+    /// Subject: [PATCH] Fix the greeting
+    ///
+    /// The old greeting was confusing.
+    ///
+    /// Fixes: 0bc302b2c6b7 (\"Add a greeting\")
+    /// Signed-off-by: phil <uniphil@gmail.com>
+    /// ---
+    /// ";
+    ///
+    /// let commit = CommitPatch::parse(sample)?;
+    /// assert_eq!(
+    ///     commit.trailers(),
+    ///     [
+    ///         ("Fixes", "0bc302b2c6b7 (\"Add a greeting\")"),
+    ///         ("Signed-off-by", "phil <uniphil@gmail.com>"),
+    ///     ]
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn trailers(&self) -> Vec<(&'a str, &'a str)> {
+        let message = self.message;
+        let paragraph = message.rfind("\n\n").map_or(message, |i| &message[i + 2..]);
+        let mut trailers = Vec::new();
+        for line in paragraph.lines() {
+            if line.starts_with(char::is_whitespace) && !trailers.is_empty() {
+                continue;
+            }
+            match line.split_once(':') {
+                Some((key, value)) if is_trailer_key(key) => trailers.push((key, value.trim())),
+                _ => return Vec::new(),
+            }
+        }
+        trailers
+    }
+}
+
+fn is_trailer_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|ch| ch.is_alphanumeric() || ch == '-')
+}
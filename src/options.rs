@@ -0,0 +1,210 @@
+use std::fmt;
+
+/// How strictly to parse patches and how much input to accept, for
+/// [`Patch::from_single_with_options`], [`Patch::from_multiple_with_options`] and the other
+/// `*_with_options` functions
+///
+/// The default is [`ParserOptions::git`], which is what [`Patch::from_multiple`] uses. Start from
+/// one of the presets and adjust single settings with the builder methods.
+///
+/// None of the presets limit the size of the input. For input from untrusted sources, the
+/// `max_*` methods set limits that make parsing stop with a [`ParseError`] whose
+/// [`limit_exceeded`](crate::ParseError::limit_exceeded) says which limit the input went over.
+///
+/// # Example
+///
+/// ```
+/// # use patch::{ParserOptions, Patch};
+/// let sample = "\
+/// Some text before the patch
+/// --- before.py
+/// +++ after.py
+/// @@ -1,1 +1,1 @@
+/// -bacon
+/// +python\n";
+///
+/// assert!(Patch::from_multiple_with_options(sample, &ParserOptions::gnu()).is_err());
+/// let options = ParserOptions::gnu().allow_junk(true);
+/// assert!(Patch::from_multiple_with_options(sample, &options).is_ok());
+/// ```
+///
+/// [`Patch::from_single_with_options`]: crate::Patch::from_single_with_options
+/// [`Patch::from_multiple_with_options`]: crate::Patch::from_multiple_with_options
+/// [`Patch::from_multiple`]: crate::Patch::from_multiple
+/// [`ParseError`]: crate::ParseError
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ParserOptions {
+    pub(crate) allow_junk: bool,
+    pub(crate) strict_counts: bool,
+    pub(crate) git_headers: bool,
+    pub(crate) strict_no_newline_indicator: bool,
+    pub(crate) quoted_escapes: EscapeSet,
+    pub(crate) max_input_len: Option<usize>,
+    pub(crate) max_patches: Option<usize>,
+    pub(crate) max_hunks: Option<usize>,
+    pub(crate) max_hunk_lines: Option<usize>,
+    pub(crate) max_line_len: Option<usize>,
+}
+
+impl ParserOptions {
+    /// Only accept plain unified diffs, like GNU `diff -u` writes: no text around the patches,
+    /// no git extended headers or git's escapes in quoted paths, and hunks must have as many
+    /// lines as their ranges say
+    pub fn gnu() -> Self {
+        ParserOptions {
+            allow_junk: false,
+            strict_counts: true,
+            git_headers: false,
+            strict_no_newline_indicator: false,
+            quoted_escapes: EscapeSet::C,
+            max_input_len: None,
+            max_patches: None,
+            max_hunks: None,
+            max_hunk_lines: None,
+            max_line_len: None,
+        }
+    }
+
+    /// Accept unified diffs with git extended headers and text around the patches, like commit
+    /// messages, but hunks must have as many lines as their ranges say
+    pub fn git() -> Self {
+        ParserOptions {
+            allow_junk: true,
+            strict_counts: true,
+            git_headers: true,
+            strict_no_newline_indicator: false,
+            quoted_escapes: EscapeSet::Git,
+            max_input_len: None,
+            max_patches: None,
+            max_hunks: None,
+            max_hunk_lines: None,
+            max_line_len: None,
+        }
+    }
+
+    /// Like [`ParserOptions::git`], but hunk line counts don't have to match their ranges, for
+    /// scraping hand-edited or mangled patches
+    ///
+    /// A hunk ends at the first line that can't be part of it, and may run past its ranges up to
+    /// the next hunk or patch header. Lines that still fit the ranges are read like with the
+    /// default options, so everything those accept parses the same. The ranges are kept as
+    /// written.
+    pub fn forgiving() -> Self {
+        ParserOptions {
+            strict_counts: false,
+            ..ParserOptions::git()
+        }
+    }
+
+    /// Whether to skip text before and between patches, like commit messages. It is kept in
+    /// [`Patch::preamble`](crate::Patch::preamble).
+    pub fn allow_junk(mut self, allow: bool) -> Self {
+        self.allow_junk = allow;
+        self
+    }
+
+    /// Whether a hunk must have exactly as many lines as its ranges say
+    pub fn strict_counts(mut self, strict: bool) -> Self {
+        self.strict_counts = strict;
+        self
+    }
+
+    /// Whether to accept the `diff --git` line and extended headers written by `git diff`
+    pub fn git_headers(mut self, accept: bool) -> Self {
+        self.git_headers = accept;
+        self
+    }
+
+    /// Whether `\ No newline at end of file` must be in English. Otherwise any line starting with
+    /// `\ ` after a hunk line is read as it, like the translations GNU diff writes in other
+    /// locales.
+    pub fn strict_no_newline_indicator(mut self, strict: bool) -> Self {
+        self.strict_no_newline_indicator = strict;
+        self
+    }
+
+    /// Which escapes to accept in the quoted paths of `---`, `+++` and `Binary files` lines. The
+    /// paths of git's own header lines, like `diff --git`, always accept [`EscapeSet::Git`].
+    /// Paths with other escapes are read as they are written, quotes included.
+    pub fn quoted_escapes(mut self, escapes: EscapeSet) -> Self {
+        self.quoted_escapes = escapes;
+        self
+    }
+
+    /// Fail with [`LimitExceeded::InputLength`] on input longer than this many bytes, before
+    /// parsing any of it
+    pub fn max_input_len(mut self, limit: usize) -> Self {
+        self.max_input_len = Some(limit);
+        self
+    }
+
+    /// Fail with [`LimitExceeded::Patches`] on input with more than this many patches
+    pub fn max_patches(mut self, limit: usize) -> Self {
+        self.max_patches = Some(limit);
+        self
+    }
+
+    /// Fail with [`LimitExceeded::Hunks`] on a patch with more than this many hunks
+    pub fn max_hunks(mut self, limit: usize) -> Self {
+        self.max_hunks = Some(limit);
+        self
+    }
+
+    /// Fail with [`LimitExceeded::HunkLines`] on a hunk with more than this many lines
+    pub fn max_hunk_lines(mut self, limit: usize) -> Self {
+        self.max_hunk_lines = Some(limit);
+        self
+    }
+
+    /// Fail with [`LimitExceeded::LineLength`] on a hunk line with more than this many bytes of
+    /// text
+    pub fn max_line_len(mut self, limit: usize) -> Self {
+        self.max_line_len = Some(limit);
+        self
+    }
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions::git()
+    }
+}
+
+/// The escapes accepted in quoted paths, for [`ParserOptions::quoted_escapes`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EscapeSet {
+    /// Only the common escapes of C strings: `\"`, `\\`, `\n`, `\t`, `\r` and `\0`
+    C,
+    /// Also the escapes git writes with its default `core.quotePath` setting: octal escapes like
+    /// `\346`, hex escapes like `\xe6`, and `\a`, `\b`, `\v` and `\f`
+    Git,
+}
+
+/// The limit of [`ParserOptions`] that the input went over, from
+/// [`ParseError::limit_exceeded`](crate::ParseError::limit_exceeded)
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LimitExceeded {
+    /// The input is longer than [`ParserOptions::max_input_len`]
+    InputLength,
+    /// There are more patches than [`ParserOptions::max_patches`]
+    Patches,
+    /// A patch has more hunks than [`ParserOptions::max_hunks`]
+    Hunks,
+    /// A hunk has more lines than [`ParserOptions::max_hunk_lines`]
+    HunkLines,
+    /// A hunk line is longer than [`ParserOptions::max_line_len`]
+    LineLength,
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = match self {
+            LimitExceeded::InputLength => "input is too long",
+            LimitExceeded::Patches => "too many patches",
+            LimitExceeded::Hunks => "too many hunks in a patch",
+            LimitExceeded::HunkLines => "too many lines in a hunk",
+            LimitExceeded::LineLength => "line is too long",
+        };
+        write!(f, "{}", what)
+    }
+}
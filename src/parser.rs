@@ -1,18 +1,37 @@
 use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
 use std::error::Error;
 
 use chrono::DateTime;
+use memchr::{memchr, memchr2};
 use nom::*;
 use nom::{
     branch::alt,
-    bytes::complete::{is_not, tag, take_until},
-    character::complete::{char, digit1, line_ending, none_of, not_line_ending, one_of},
-    combinator::{map, not, opt},
-    multi::{many0, many1},
-    sequence::{delimited, preceded, terminated, tuple},
+    bytes::complete::{is_a, is_not, tag, take, take_while_m_n},
+    character::complete::{char, digit1, hex_digit1, line_ending, oct_digit1, one_of},
+    combinator::{
+        all_consuming, cond, consumed, cut, eof, map, map_opt, map_res, not, opt, peek, recognize,
+        value, verify,
+    },
+    multi::{count, fold_many0, fold_many1, many0, many0_count, many1, separated_list1},
+    sequence::{delimited, preceded, separated_pair, terminated, tuple},
 };
 
+use crate::ansi::strip_ansi_colors_in_place;
 use crate::ast::*;
+use crate::base85;
+use crate::ed::{EdCommand, EdCommandKind, EdScript};
+use crate::events::{FileHeaderEvent, PatchEvent};
+use crate::mail::CommitPatch;
+use crate::options::{EscapeSet, LimitExceeded, ParserOptions};
+use crate::range_diff::{
+    RangeDiff, RangeDiffCommit, RangeDiffEntry, RangeDiffSection, RangeDiffStatus,
+};
+use crate::scan::{HunkHeader, PatchHeaders};
+use crate::series::{Series, SeriesEntry};
+use crate::stats::FileStat;
+use crate::warning::{Warning, WarningKind};
+use crate::word_diff::{WordDiffHunk, WordDiffLine, WordDiffPatch};
 
 type Input<'a> = nom_locate::LocatedSpan<&'a str>;
 
@@ -27,6 +46,9 @@ pub struct ParseError<'a> {
     pub fragment: &'a str,
     /// The actual parsing error
     pub kind: nom::error::ErrorKind,
+    /// The limit of the [`ParserOptions`] that the input went over, if that stopped parsing. The
+    /// `kind` is then [`TooLarge`](nom::error::ErrorKind::TooLarge).
+    pub limit_exceeded: Option<LimitExceeded>,
 }
 
 #[doc(hidden)]
@@ -40,6 +62,7 @@ impl<'a> From<nom::Err<nom::error::Error<Input<'a>>>> for ParseError<'a> {
                 offset: error.input.location_offset(),
                 fragment: error.input.fragment(),
                 kind: error.code,
+                limit_exceeded: None,
             },
         }
     }
@@ -47,6 +70,9 @@ impl<'a> From<nom::Err<nom::error::Error<Input<'a>>>> for ParseError<'a> {
 
 impl<'a> std::fmt::Display for ParseError<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(limit) = self.limit_exceeded {
+            return write!(f, "Line {}: Limit exceeded: {}", self.line, limit);
+        }
         write!(
             f,
             "Line {}: Error while parsing: {}",
@@ -61,79 +87,1467 @@ impl<'a> Error for ParseError<'a> {
     }
 }
 
+// Like nom's `not_line_ending`, which checks one char at a time, but searching for the end of the
+// line with memchr, since most of a patch is read through this
+fn not_line_ending(input: Input<'_>) -> IResult<Input<'_>, Input<'_>> {
+    let bytes = input.fragment().as_bytes();
+    let end = match memchr2(b'\r', b'\n', bytes) {
+        // A `\r` that isn't part of a `\r\n` line ending can't end the line
+        Some(i) if bytes[i] == b'\r' && bytes.get(i + 1) != Some(&b'\n') => {
+            return Err(nom::Err::Error(error::Error::new(
+                input.take_split(i).0,
+                error::ErrorKind::Tag,
+            )))
+        }
+        Some(i) => i,
+        None => bytes.len(),
+    };
+    Ok(input.take_split(end))
+}
+
 fn consume_content_line(input: Input<'_>) -> IResult<Input<'_>, &str> {
     let (input, raw) = terminated(not_line_ending, line_ending)(input)?;
     Ok((input, raw.fragment()))
 }
 
-pub(crate) fn parse_single_patch(s: &str) -> Result<Patch, ParseError<'_>> {
-    let (remaining_input, patch) = patch(Input::new(s))?;
-    // Parser should return an error instead of producing remaining input
-    assert!(
-        remaining_input.fragment().is_empty(),
-        "bug: failed to parse entire input. \
-        Remaining: '{}'",
-        remaining_input.fragment()
-    );
+// Like `consume_content_line`, but the last line may be missing its line ending
+fn content_line(input: Input<'_>) -> IResult<Input<'_>, &str> {
+    let (input, raw) = terminated(not_line_ending, alt((line_ending, eof)))(input)?;
+    Ok((input, raw.fragment()))
+}
+
+// The options of a parse, and warnings about what it worked around so far
+#[derive(Default)]
+struct ParseContext {
+    options: ParserOptions,
+    warnings: RefCell<Vec<Warning>>,
+    patches: Cell<usize>,
+    limit_exceeded: Cell<Option<LimitExceeded>>,
+}
+
+impl ParseContext {
+    fn new(options: &ParserOptions) -> Self {
+        ParseContext {
+            options: *options,
+            ..ParseContext::default()
+        }
+    }
+
+    // Stops the whole parse at `input` because it went over a limit, without backtracking
+    fn exceeded<'a, T>(&self, limit: LimitExceeded, input: Input<'a>) -> IResult<Input<'a>, T> {
+        self.limit_exceeded.set(Some(limit));
+        Err(nom::Err::Failure(error::Error::new(
+            input,
+            error::ErrorKind::TooLarge,
+        )))
+    }
+
+    // Fails if the whole input is over the size limit, before any of it is parsed
+    fn check_input_len<'a>(&self, input: Input<'a>) -> IResult<Input<'a>, ()> {
+        match self.options.max_input_len {
+            Some(max) if input.fragment().len() > max => {
+                self.exceeded(LimitExceeded::InputLength, input)
+            }
+            _ => Ok((input, ())),
+        }
+    }
+
+    fn error<'a>(&self, err: nom::Err<error::Error<Input<'a>>>) -> ParseError<'a> {
+        ParseError {
+            limit_exceeded: self.limit_exceeded.get(),
+            ..err.into()
+        }
+    }
+
+    fn into_warnings(self) -> Vec<Warning> {
+        let mut warnings = self.warnings.into_inner();
+        warnings.sort_by_key(|warning| warning.span.start);
+        warnings
+    }
+}
+
+// Records a warning about the input between `start` and `end`. Parsers only record warnings once
+// they can't fail anymore, so that a parser that backtracks leaves none behind.
+fn warning(kind: WarningKind, start: Input<'_>, end: Input<'_>) -> Warning {
+    Warning {
+        kind,
+        span: span_between(start, end),
+    }
+}
+
+pub(crate) fn parse_single_patch<'a>(
+    s: &'a str,
+    options: &ParserOptions,
+) -> Result<Patch<'a>, ParseError<'a>> {
+    let cx = ParseContext::new(options);
+    let (_, (patch, _)) = all_consuming(preceded(
+        |input| cx.check_input_len(input),
+        |input| spanned_patch(input, &cx),
+    ))(Input::new(s))
+    .map_err(|err| cx.error(err))?;
     Ok(patch)
 }
 
-pub(crate) fn parse_multiple_patches(s: &str) -> Result<Vec<Patch>, ParseError<'_>> {
-    let (remaining_input, patches) = multiple_patches(Input::new(s))?;
-    // Parser should return an error instead of producing remaining input
-    assert!(
-        remaining_input.fragment().is_empty(),
-        "bug: failed to parse entire input. \
-        Remaining: '{}'",
-        remaining_input.fragment()
-    );
+pub(crate) fn parse_multiple_patches<'a>(
+    s: &'a str,
+    options: &ParserOptions,
+) -> Result<Vec<Patch<'a>>, ParseError<'a>> {
+    let (patches, _) = parse_multiple_patches_with_warnings(s, options)?;
     Ok(patches)
 }
 
-fn multiple_patches(input: Input<'_>) -> IResult<Input<'_>, Vec<Patch>> {
-    many1(patch)(input)
+pub(crate) fn parse_multiple_patches_with_warnings<'a>(
+    s: &'a str,
+    options: &ParserOptions,
+) -> Result<(Vec<Patch<'a>>, Vec<Warning>), ParseError<'a>> {
+    let cx = ParseContext::new(options);
+    // A hunk with more lines than its ranges say leaves input that isn't a patch
+    let (_, patches) = all_consuming(preceded(
+        |input| cx.check_input_len(input),
+        many1(map(|input| spanned_patch(input, &cx), |(patch, _)| patch)),
+    ))(Input::new(s))
+    .map_err(|err| cx.error(err))?;
+    Ok((patches, cx.into_warnings()))
 }
 
-fn patch(input: Input<'_>) -> IResult<Input<'_>, Patch> {
-    let (input, files) = headers(input)?;
-    let (input, hunks) = chunks(input)?;
-    let (input, no_newline_indicator) = no_newline_indicator(input)?;
+pub(crate) fn iter_multiple_patches<'a>(
+    s: &'a str,
+    options: &ParserOptions,
+) -> impl Iterator<Item = Result<Patch<'a>, ParseError<'a>>> {
+    let cx = ParseContext::new(options);
+    let mut input = Some(Input::new(s));
+    let mut first = true;
+    std::iter::from_fn(move || {
+        let current = input.take()?;
+        // Like `from_multiple`, input without any patch is an error
+        if !first && current.fragment().is_empty() {
+            return None;
+        }
+        // Nobody reads the warnings, so don't keep them around while iterating
+        cx.warnings.borrow_mut().clear();
+        let parsed = match first {
+            true => preceded(
+                |input| cx.check_input_len(input),
+                |input| spanned_patch(input, &cx),
+            )(current),
+            false => spanned_patch(current, &cx),
+        };
+        first = false;
+        match parsed {
+            Ok((rest, (patch, _))) => {
+                input = Some(rest);
+                Some(Ok(patch))
+            }
+            Err(err) if cx.limit_exceeded.get().is_some() => Some(Err(cx.error(err))),
+            Err(err) => Some(Err(patch_error(current, err))),
+        }
+    })
+}
+
+pub(crate) fn parse_git_diff_output<'a>(
+    bytes: &'a mut [u8],
+    options: &ParserOptions,
+) -> Result<Vec<Patch<'a>>, ParseError<'a>> {
+    let len = strip_ansi_colors_in_place(bytes);
+    let bytes = &bytes[..len];
+    let s = std::str::from_utf8(bytes).map_err(|err| {
+        let valid = &bytes[..err.valid_up_to()];
+        ParseError {
+            line: valid.iter().filter(|&&b| b == b'\n').count() as u32 + 1,
+            offset: err.valid_up_to(),
+            fragment: "",
+            kind: nom::error::ErrorKind::Char,
+            limit_exceeded: None,
+        }
+    })?;
+    let cx = ParseContext::new(options);
+    let (mut input, _) = cx
+        .check_input_len(Input::new(s))
+        .map_err(|err| cx.error(err))?;
+    let mut patches = Vec::new();
+    loop {
+        let (rest, (patch, _)) = match spanned_patch(input, &cx) {
+            Ok(parsed) => parsed,
+            Err(err) if cx.limit_exceeded.get().is_some() => return Err(cx.error(err)),
+            Err(_) => break,
+        };
+        // Only commits follow a patch, so a line like `+c` is a hunk line beyond the counts
+        let line: &str = rest.fragment();
+        if line.starts_with(['+', '-', ' ', '\\']) && !line.starts_with("--- ") {
+            return Err(trailing_text_error(rest));
+        }
+        patches.push(patch);
+        input = rest;
+    }
+    // Anything left must be text without patches, e.g. the header of a commit without changes
+    let (input, _) = preamble(input)?;
+    if preamble_end(input).is_ok() {
+        // Parsing stopped at a patch that is invalid, so report why
+        spanned_patch(input, &cx)?;
+    }
+    Ok(patches)
+}
+
+pub(crate) fn parse_combined_patches(s: &str) -> Result<Vec<CombinedPatch<'_>>, ParseError<'_>> {
+    let (_, patches) = all_consuming(many1(combined_patch))(Input::new(s))?;
+    Ok(patches)
+}
+
+pub(crate) fn parse_word_diff_patches(s: &str) -> Result<Vec<WordDiffPatch<'_>>, ParseError<'_>> {
+    let (_, patches) = all_consuming(many1(word_diff_patch))(Input::new(s))?;
+    Ok(patches)
+}
+
+pub(crate) fn parse_ed_script(s: &str) -> Result<EdScript<'_>, ParseError<'_>> {
+    let (_, commands) = all_consuming(many0(ed_command))(Input::new(s))?;
+    Ok(EdScript { commands })
+}
+
+pub(crate) fn parse_series(s: &str) -> Result<Series<'_>, ParseError<'_>> {
+    let (_, entries) = all_consuming(many0(map_opt(
+        terminated(not_line_ending, line_ending),
+        series_entry,
+    )))(Input::new(s))?;
+    Ok(Series {
+        entries: entries.into_iter().flatten().collect(),
+    })
+}
+
+pub(crate) fn parse_commit_patch(s: &str) -> Result<CommitPatch<'_>, ParseError<'_>> {
+    let (_, commit) = all_consuming(commit_patch)(Input::new(s))?;
+    Ok(commit)
+}
+
+pub(crate) fn parse_mbox(s: &str) -> Result<Vec<CommitPatch<'_>>, ParseError<'_>> {
+    let (_, commits) = all_consuming(many1(mbox_message))(Input::new(s))?;
+    Ok(commits)
+}
+
+pub(crate) fn parse_numstat(s: &str) -> Result<Vec<FileStat<'_>>, ParseError<'_>> {
+    let (_, stats) = all_consuming(many0(numstat_line))(Input::new(s))?;
+    Ok(stats)
+}
+
+pub(crate) fn parse_stat(s: &str) -> Result<Vec<FileStat<'_>>, ParseError<'_>> {
+    let (_, stats) = all_consuming(terminated(
+        many0(map_opt(preceded(char(' '), content_line), stat_line)),
+        // e.g. ` 3 files changed, 202 insertions(+)`
+        opt(preceded(char(' '), content_line)),
+    ))(Input::new(s))?;
+    Ok(stats)
+}
+
+pub(crate) fn parse_range_diff(s: &str) -> Result<RangeDiff<'_>, ParseError<'_>> {
+    let (_, entries) = all_consuming(many0(range_diff_entry))(Input::new(s))?;
+    Ok(RangeDiff { entries })
+}
+
+pub(crate) fn parse_hunk(s: &str) -> Result<Hunk<'_>, ParseError<'_>> {
+    let (_, hunk) = all_consuming(chunk)(Input::new(s))?;
+    Ok(hunk)
+}
+
+pub(crate) fn parse_hunk_fragment(s: &str) -> Result<Hunk<'_>, ParseError<'_>> {
+    let (_, hunk) = all_consuming(fragment(chunk))(Input::new(s))?;
+    Ok(hunk)
+}
+
+pub(crate) fn parse_headerless_patch<'a>(
+    s: &'a str,
+    old_path: &'a str,
+    new_path: &'a str,
+) -> Result<Patch<'a>, ParseError<'a>> {
+    let input = Input::new(s);
+    let (_, (hunks, old_end_newline, new_end_newline)) =
+        all_consuming(fragment(|input| chunks(input, &ParseContext::default())))(input)?;
+    let file = |path: &'a str| File {
+        path: path.into(),
+        escaped: false,
+        meta: None,
+    };
+    Ok(Patch {
+        old: file(old_path),
+        new: file(new_path),
+        git: None,
+        svn: None,
+        cvs: None,
+        perforce: None,
+        hunks,
+        binary: None,
+        old_end_newline,
+        new_end_newline,
+        line_ending: first_line_ending(input),
+        byte_order_mark: false,
+        preamble: None,
+    })
+}
+
+pub(crate) fn parse_normal_patch<'a>(
+    s: &'a str,
+    old_path: &'a str,
+    new_path: &'a str,
+) -> Result<Patch<'a>, ParseError<'a>> {
+    let input = Input::new(s);
+    let (_, (hunks, old_end_newline, new_end_newline)) = all_consuming(fold_many1(
+        normal_chunk,
+        || (Vec::new(), true, true),
+        |(mut hunks, old_acc, new_acc), (hunk, old_end_newline, new_end_newline)| {
+            hunks.push(hunk);
+            (
+                hunks,
+                old_acc && old_end_newline,
+                new_acc && new_end_newline,
+            )
+        },
+    ))(input)?;
+    let file = |path: &'a str| File {
+        path: path.into(),
+        escaped: false,
+        meta: None,
+    };
+    Ok(Patch {
+        old: file(old_path),
+        new: file(new_path),
+        git: None,
+        svn: None,
+        cvs: None,
+        perforce: None,
+        hunks,
+        binary: None,
+        old_end_newline,
+        new_end_newline,
+        line_ending: first_line_ending(input),
+        byte_order_mark: false,
+        preamble: None,
+    })
+}
+
+// Hunks quoted in e.g. a code review comment, which may have empty lines around them
+fn fragment<'a, O>(
+    parser: impl FnMut(Input<'a>) -> IResult<Input<'a>, O>,
+) -> impl FnMut(Input<'a>) -> IResult<Input<'a>, O> {
+    delimited(many0(line_ending), parser, many0(line_ending))
+}
+
+pub(crate) fn parse_header_line(s: &str) -> Result<File<'_>, ParseError<'_>> {
+    let (_, file) = all_consuming(delimited(
+        alt((tag("--- "), tag("+++ "))),
+        |input| header_line_content(input, EscapeSet::Git),
+        opt(line_ending),
+    ))(Input::new(s))?;
+    Ok(file)
+}
+
+pub(crate) fn parse_recursive_diff<'a>(
+    s: &'a str,
+    options: &ParserOptions,
+) -> Result<Vec<DiffEntry<'a>>, ParseError<'a>> {
+    let cx = ParseContext::new(options);
+    let entries = many1(alt((
+        map(only_in, |(dir, name)| DiffEntry::OnlyIn { dir, name }),
+        map(files_differ, |(old, new)| DiffEntry::BinaryDiffer {
+            escaped: old.escaped || new.escaped,
+            old: old.path,
+            new: new.path,
+        }),
+        map(
+            |input| spanned_patch(input, &cx),
+            |(patch, _)| DiffEntry::Patch(patch),
+        ),
+    )));
+    let (_, entries) =
+        all_consuming(preceded(|input| cx.check_input_len(input), entries))(Input::new(s))
+            .map_err(|err| cx.error(err))?;
+    Ok(entries)
+}
+
+pub(crate) fn parse_multiple_patches_with_spans<'a>(
+    s: &'a str,
+    options: &ParserOptions,
+) -> Result<Vec<(Patch<'a>, Span)>, ParseError<'a>> {
+    let cx = ParseContext::new(options);
+    let (_, patches) = all_consuming(preceded(
+        |input| cx.check_input_len(input),
+        many1(|input| spanned_patch(input, &cx)),
+    ))(Input::new(s))
+    .map_err(|err| cx.error(err))?;
+    Ok(patches)
+}
+
+pub(crate) fn parse_multiple_patches_lossy<'a>(
+    s: &'a str,
+    options: &ParserOptions,
+) -> (Vec<Patch<'a>>, Vec<(Span, ParseError<'a>)>) {
+    let cx = ParseContext::new(options);
+    let mut patches = Vec::new();
+    let mut errors = Vec::new();
+    let mut input = Input::new(s);
+    if let Err(err) = cx.check_input_len(input) {
+        let (end, _) = input.take_split(s.len());
+        return (patches, vec![(span_between(input, end), cx.error(err))]);
+    }
+    while !input.fragment().is_empty() {
+        match spanned_patch(input, &cx) {
+            Ok((rest, (patch, _))) => {
+                patches.push(patch);
+                input = rest;
+            }
+            // Nothing after a limit is parsed, so it can't be skipped like a broken patch
+            Err(err) if cx.limit_exceeded.get().is_some() => {
+                let (end, _) = input.take_split(input.fragment().len());
+                errors.push((span_between(input, end), cx.error(err)));
+                break;
+            }
+            Err(err) => {
+                let (start, _) = preamble(input).unwrap_or((input, 0));
+                if start.fragment().is_empty() {
+                    errors.push((span_between(input, start), trailing_text_error(input)));
+                    break;
+                }
+                // Skip the broken patch up to the start of the next one
+                let end = next_patch_start(start);
+                errors.push((span_between(start, end), err.into()));
+                input = end;
+            }
+        }
+    }
+    (patches, errors)
+}
+
+pub(crate) fn parse_multiple_patches_partial<'a>(
+    s: &'a str,
+    options: &ParserOptions,
+) -> (Vec<Patch<'a>>, Option<ParseError<'a>>) {
+    let cx = ParseContext::new(options);
+    let mut patches = Vec::new();
+    let mut input = Input::new(s);
+    if let Err(err) = cx.check_input_len(input) {
+        return (patches, Some(cx.error(err)));
+    }
+    loop {
+        match spanned_patch(input, &cx) {
+            Ok((rest, (patch, _))) => {
+                patches.push(patch);
+                if rest.fragment().is_empty() {
+                    return (patches, None);
+                }
+                input = rest;
+            }
+            Err(err) if cx.limit_exceeded.get().is_some() => return (patches, Some(cx.error(err))),
+            Err(err) => return (patches, Some(patch_error(input, err))),
+        }
+    }
+}
+
+pub(crate) fn parse_patch_headers<'a>(
+    s: &'a str,
+    options: &ParserOptions,
+) -> Result<Vec<PatchHeaders<'a>>, ParseError<'a>> {
+    let cx = ParseContext::new(options);
+    let (_, patches) = all_consuming(preceded(
+        |input| cx.check_input_len(input),
+        many1(|input| patch_headers(input, &cx)),
+    ))(Input::new(s))
+    .map_err(|err| cx.error(err))?;
+    Ok(patches)
+}
+
+// The error for a patch that failed to parse at `input`. Text that doesn't start another patch,
+// like hunk lines beyond the counts of the last hunk, only fails at the end of the input where its
+// headers are missing, so it's reported at its first line instead.
+fn patch_error<'a>(input: Input<'a>, err: nom::Err<error::Error<Input<'a>>>) -> ParseError<'a> {
+    match preamble(input) {
+        Ok((rest, _)) if rest.fragment().is_empty() => trailing_text_error(input),
+        _ => err.into(),
+    }
+}
+
+// An error at the first line of text that was left over after the patches
+fn trailing_text_error(input: Input<'_>) -> ParseError<'_> {
+    let s = input.fragment();
+    let (_, line) = input.take_split(memchr(b'\n', s.as_bytes()).unwrap_or(s.len()));
+    nom::Err::Error(error::Error::new(line, error::ErrorKind::Eof)).into()
+}
+
+// The start of the patch after the one starting at `input`, or the end of the input. This
+// always skips at least the first line.
+fn next_patch_start(input: Input<'_>) -> Input<'_> {
+    let s = input.fragment();
+    // git writes a `diff --git` line for every patch, which is a more reliable boundary than the
+    // `---` lines of other diff tools
+    let is_start = |line: &str| {
+        if s.starts_with("diff --git ") {
+            line.starts_with("diff --git ")
+        } else {
+            preamble_end(Input::new(line)).is_ok()
+        }
+    };
+    let mut offset = 0;
+    for (i, line) in s.split_inclusive('\n').enumerate() {
+        if i > 0 && is_start(line) {
+            break;
+        }
+        offset += line.len();
+    }
+    let (rest, _) = input.take_split(offset);
+    rest
+}
+
+fn patch(input: Input<'_>) -> IResult<Input<'_>, Patch<'_>> {
+    map(
+        |input| spanned_patch(input, &ParseContext::default()),
+        |(patch, _)| patch,
+    )(input)
+}
+
+fn spanned_patch<'a>(input: Input<'a>, cx: &ParseContext) -> IResult<Input<'a>, (Patch<'a>, Span)> {
+    let options = &cx.options;
+    let (input, byte_order_mark) = map(opt(char('\u{feff}')), |bom| bom.is_some())(input)?;
+    // Keep any preamble lines in produced diffs, but don't parse them
+    let preamble_start = input;
+    let (input, preamble) = verify(recognize(preamble), |preamble: &Input<'_>| {
+        options.allow_junk || preamble.fragment().is_empty()
+    })(input)?;
+    let preamble = Some(*preamble.fragment()).filter(|preamble| !preamble.is_empty());
+    let start = input;
+    let ending = first_line_ending(input);
+    let (input, headers) = file_headers(input, options)?;
+    let FileHeaders {
+        old,
+        new,
+        git,
+        index_path,
+        cvs,
+        perforce,
+        binary,
+        body,
+    } = headers;
+    let (input, (hunks, old_end_newline, new_end_newline)) = match body {
+        PatchBody::Empty => (input, (Vec::new(), true, true)),
+        PatchBody::Hunks { required: true, .. } => chunks(input, cx)?,
+        PatchBody::Hunks { .. } => {
+            let (input, hunks) = opt(|input| chunks(input, cx))(input)?;
+            (input, hunks.unwrap_or((Vec::new(), true, true)))
+        }
+    };
+    let (input, svn) = match index_path {
+        Some(path) if cvs.is_none() => {
+            let (input, property_changes) = opt(svn_property_changes)(input)?;
+            let property_changes = property_changes.unwrap_or_default();
+            let svn = SvnMetadata {
+                path,
+                property_changes,
+            };
+            (input, Some(svn))
+        }
+        _ => (input, None),
+    };
+    let span = span_between(start, input);
+    let patches = cx.patches.get() + 1;
+    if options.max_patches.is_some_and(|max| patches > max) {
+        return cx.exceeded(LimitExceeded::Patches, start);
+    }
+    cx.patches.set(patches);
+    if preamble.is_some() {
+        let skipped = warning(WarningKind::Preamble, preamble_start, start);
+        cx.warnings.borrow_mut().push(skipped);
+    }
+    if !hunks.is_empty() {
+        let (rest, _) = extra_hunk_lines(input)?;
+        if rest.location_offset() > input.location_offset() {
+            let extra = warning(WarningKind::ExtraHunkLines, input, rest);
+            cx.warnings.borrow_mut().push(extra);
+        }
+    }
     // Ignore trailing empty lines produced by some diff programs
     let (input, _) = many0(line_ending)(input)?;
 
-    let (old, new) = files;
     Ok((
         input,
-        Patch {
+        (
+            Patch {
+                old,
+                new,
+                git,
+                svn,
+                cvs,
+                perforce,
+                hunks,
+                binary,
+                old_end_newline,
+                new_end_newline,
+                line_ending: ending,
+                byte_order_mark,
+                preamble,
+            },
+            span,
+        ),
+    ))
+}
+
+// Like `spanned_patch`, but only the headers, skipping the lines of each hunk
+fn patch_headers<'a>(input: Input<'a>, cx: &ParseContext) -> IResult<Input<'a>, PatchHeaders<'a>> {
+    let options = &cx.options;
+    let (input, _) = opt(char('\u{feff}'))(input)?;
+    let (input, _) = verify(recognize(preamble), |preamble: &Input<'_>| {
+        options.allow_junk || preamble.fragment().is_empty()
+    })(input)?;
+    let start = input;
+    let (input, headers) = file_headers(input, options)?;
+    let FileHeaders {
+        old,
+        new,
+        git,
+        body,
+        ..
+    } = headers;
+    let parsed = Cell::new(0);
+    let chunk = |input| {
+        let hunk = skipped_chunk(input, cx)?;
+        parsed.set(parsed.get() + 1);
+        if options.max_hunks.is_some_and(|max| parsed.get() > max) {
+            return cx.exceeded(LimitExceeded::Hunks, input);
+        }
+        Ok(hunk)
+    };
+    let (input, hunks) = match body {
+        PatchBody::Empty => (input, Vec::new()),
+        PatchBody::Hunks { required: true, .. } => many1(chunk)(input)?,
+        PatchBody::Hunks { svn, .. } => {
+            let (input, hunks) = many0(chunk)(input)?;
+            let (input, _) = cond(svn, opt(svn_property_changes))(input)?;
+            (input, hunks)
+        }
+    };
+    let patches = cx.patches.get() + 1;
+    if options.max_patches.is_some_and(|max| patches > max) {
+        return cx.exceeded(LimitExceeded::Patches, start);
+    }
+    cx.patches.set(patches);
+    let (input, _) = many0(line_ending)(input)?;
+    Ok((
+        input,
+        PatchHeaders {
             old,
             new,
+            git,
             hunks,
-            end_newline: !no_newline_indicator,
         },
-    ))
+    ))
+}
+
+// What follows the headers of a patch, from `file_headers`
+#[derive(Debug, Clone, Copy)]
+enum PatchBody {
+    // Nothing, like for binary files and git patches that only change metadata
+    Empty,
+    // Hunks, which plain unified diffs must have, followed by property changes for svn patches
+    Hunks { required: bool, svn: bool },
+}
+
+// The headers of a patch after its preamble, up to its hunks, from `file_headers`
+struct FileHeaders<'a> {
+    old: File<'a>,
+    new: File<'a>,
+    git: Option<GitMetadata<'a>>,
+    // The path of the `Index:` line written by svn and cvs
+    index_path: Option<Cow<'a, str>>,
+    cvs: Option<CvsMetadata<'a>>,
+    perforce: Option<PerforceMetadata<'a>>,
+    binary: Option<BinaryPatch>,
+    body: PatchBody,
+}
+
+// The headers of a patch after its preamble, up to its hunks, in any of the dialects
+fn file_headers<'a>(
+    input: Input<'a>,
+    options: &ParserOptions,
+) -> IResult<Input<'a>, FileHeaders<'a>> {
+    let escapes = options.quoted_escapes;
+    let (input, git) = if options.git_headers {
+        opt(git_header)(input)?
+    } else {
+        (input, None)
+    };
+    let (input, index_path) = match git {
+        None => opt(index_header)(input)?,
+        Some(_) => (input, None),
+    };
+    let (input, cvs) = match &index_path {
+        Some(path) => opt(|input| cvs_header(input, path.clone()))(input)?,
+        None => (input, None),
+    };
+    let (input, perforce) = match (&git, &index_path) {
+        (None, None) => opt(perforce_header)(input)?,
+        _ => (input, None),
+    };
+    let hunks = |required, svn| PatchBody::Hunks { required, svn };
+    let (input, (old, new, binary, body)) = match (&git, &index_path, &perforce) {
+        _ if input.fragment().starts_with("Binary files ") => {
+            let (input, (old, new)) = binary_files_differ(input, escapes)?;
+            (
+                input,
+                (old, new, Some(BinaryPatch::Changed), PatchBody::Empty),
+            )
+        }
+        // git leaves out the file headers and hunks when only metadata changed, e.g. renames
+        (Some(git), _, _) if !input.fragment().starts_with("--- ") => {
+            let old = File {
+                path: git.old_path.clone(),
+                escaped: git.escaped_paths,
+                meta: None,
+            };
+            let new = File {
+                path: git.new_path.clone(),
+                escaped: git.escaped_paths,
+                meta: None,
+            };
+            let (input, binary) = opt(binary_patch)(input)?;
+            (input, (old, new, binary, PatchBody::Empty))
+        }
+        // Perforce writes its own header line instead of the file headers, and `p4 describe`
+        // an empty line after it
+        (_, _, Some(perforce)) => {
+            let (old, new) = perforce_files(perforce);
+            let (input, _) = many0(line_ending)(input)?;
+            (input, (old, new, None, hunks(false, false)))
+        }
+        // Subversion writes the file headers without hunks when only properties changed, and
+        // versions before 1.7 leave out the file headers too
+        (_, Some(path), _) => {
+            let (input, files) = opt(|input| headers(input, escapes))(input)?;
+            let (old, new) = files.unwrap_or_else(|| {
+                let file = File {
+                    path: path.clone(),
+                    escaped: false,
+                    meta: None,
+                };
+                (file.clone(), file)
+            });
+            (input, (old, new, None, hunks(false, cvs.is_none())))
+        }
+        _ => {
+            let (input, (old, new)) = headers(input, escapes)?;
+            (input, (old, new, None, hunks(true, false)))
+        }
+    };
+    Ok((
+        input,
+        FileHeaders {
+            old,
+            new,
+            git,
+            index_path,
+            cvs,
+            perforce,
+            binary,
+            body,
+        },
+    ))
+}
+
+// Reads a patch file one event at a time for `PatchEvents`, checking hunk line counts like
+// `spanned_patch` but without collecting anything
+#[derive(Debug, Clone)]
+pub(crate) struct EventParser<'a> {
+    input: Input<'a>,
+    state: EventState,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum EventState {
+    // Before the first patch, which must exist
+    Start,
+    // Between two patches
+    Headers,
+    Hunks {
+        body: PatchBody,
+        ending: LineEnding,
+    },
+    Lines {
+        old_lines: u64,
+        new_lines: u64,
+        body: PatchBody,
+        ending: LineEnding,
+    },
+    Done,
+}
+
+impl<'a> EventParser<'a> {
+    pub(crate) fn new(s: &'a str) -> Self {
+        EventParser {
+            input: Input::new(s),
+            state: EventState::Start,
+        }
+    }
+
+    pub(crate) fn next_event(&mut self) -> Option<Result<PatchEvent<'a>, ParseError<'a>>> {
+        match self.event() {
+            Ok((input, event)) => {
+                self.input = input;
+                event.map(Ok)
+            }
+            Err(err) => {
+                self.state = EventState::Done;
+                Some(Err(err.into()))
+            }
+        }
+    }
+
+    fn event(&mut self) -> IResult<Input<'a>, Option<PatchEvent<'a>>> {
+        let input = self.input;
+        match self.state {
+            EventState::Done => Ok((input, None)),
+            EventState::Headers if input.fragment().is_empty() => {
+                self.state = EventState::Done;
+                Ok((input, None))
+            }
+            EventState::Start | EventState::Headers => {
+                let (input, _) = opt(char('\u{feff}'))(input)?;
+                let (start, _) = preamble(input)?;
+                let (rest, headers) = file_headers(start, &ParserOptions::default())?;
+                self.state = EventState::Hunks {
+                    body: headers.body,
+                    ending: first_line_ending(start),
+                };
+                let header = FileHeaderEvent {
+                    old: headers.old,
+                    new: headers.new,
+                    git: headers.git,
+                };
+                Ok((rest, Some(PatchEvent::FileHeader(Box::new(header)))))
+            }
+            EventState::Hunks { body, ending } => {
+                if let PatchBody::Hunks { required, svn } = body {
+                    match chunk_header(input) {
+                        Ok((rest, (old_range, new_range, range_hint))) => {
+                            self.state = EventState::Lines {
+                                old_lines: old_range.count,
+                                new_lines: new_range.count,
+                                body: PatchBody::Hunks {
+                                    required: false,
+                                    svn,
+                                },
+                                ending,
+                            };
+                            let header = HunkHeader {
+                                old_range,
+                                new_range,
+                                range_hint,
+                            };
+                            return Ok((rest, Some(PatchEvent::HunkHeader(header))));
+                        }
+                        Err(err) if required => return Err(err),
+                        Err(_) => {}
+                    }
+                }
+                let (input, _) = match body {
+                    PatchBody::Hunks { svn: true, .. } => opt(svn_property_changes)(input)?,
+                    _ => (input, None),
+                };
+                let (input, _) = many0(line_ending)(input)?;
+                self.state = EventState::Headers;
+                Ok((input, Some(PatchEvent::EndOfFile)))
+            }
+            EventState::Lines {
+                old_lines: 0,
+                new_lines: 0,
+                body,
+                ending,
+            } => {
+                self.state = EventState::Hunks { body, ending };
+                self.event()
+            }
+            EventState::Lines {
+                old_lines,
+                new_lines,
+                body,
+                ending,
+            } => {
+                let (input, line) = chunk_line(input, old_lines, new_lines, ending)?;
+                let (old_lines, new_lines) = match line {
+                    Line::Add(_) => (old_lines, new_lines - 1),
+                    Line::Remove(_) => (old_lines - 1, new_lines),
+                    Line::Context(_) => (old_lines - 1, new_lines - 1),
+                };
+                // Like in `delimited_chunk`, the indicator can only follow the last line of a side
+                let (input, no_newline) = no_newline_indicator(input)?;
+                let last = match line {
+                    Line::Add(_) => new_lines == 0,
+                    Line::Remove(_) => old_lines == 0,
+                    Line::Context(_) => old_lines == 0 && new_lines == 0,
+                };
+                if no_newline && !last {
+                    return Err(nom::Err::Error(error::Error::new(
+                        input,
+                        error::ErrorKind::Verify,
+                    )));
+                }
+                self.state = EventState::Lines {
+                    old_lines,
+                    new_lines,
+                    body,
+                    ending,
+                };
+                Ok((input, Some(PatchEvent::Line(line))))
+            }
+        }
+    }
+}
+
+// A hunk header, followed by as many lines as its ranges say, which are skipped without parsing
+// them like `chunk_with_end_newlines` does
+fn skipped_chunk<'a>(input: Input<'a>, cx: &ParseContext) -> IResult<Input<'a>, HunkHeader<'a>> {
+    let options = &cx.options;
+    let (input, (old_range, new_range, range_hint)) = chunk_header(input)?;
+    let (mut old_lines, mut new_lines) = (old_range.count, new_range.count);
+    let s = input.fragment();
+    let mut offset = 0;
+    let mut skipped = 0;
+    loop {
+        let rest = &s[offset..];
+        let len = rest.find('\n').map_or(rest.len(), |i| i + 1);
+        if !rest.starts_with('\\') && (old_lines > 0 || new_lines > 0) {
+            skipped += 1;
+            // The text of the line leaves out its first char and line ending
+            let text = rest[..len]
+                .trim_end_matches(['\r', '\n'])
+                .len()
+                .saturating_sub(1);
+            let exceeded = if options.max_hunk_lines.is_some_and(|max| skipped > max) {
+                Some(LimitExceeded::HunkLines)
+            } else if options.max_line_len.is_some_and(|max| text > max) {
+                Some(LimitExceeded::LineLength)
+            } else {
+                None
+            };
+            if let Some(limit) = exceeded {
+                let (rest, _) = input.take_split(offset);
+                return cx.exceeded(limit, rest);
+            }
+        }
+        match rest.as_bytes().first() {
+            Some(b'\\') => {}
+            _ if old_lines == 0 && new_lines == 0 => break,
+            Some(b'+') if new_lines > 0 => new_lines -= 1,
+            Some(b'-') if old_lines > 0 => old_lines -= 1,
+            Some(b' ' | b'\r' | b'\n') if old_lines > 0 && new_lines > 0 => {
+                old_lines -= 1;
+                new_lines -= 1;
+            }
+            _ => {
+                let (rest, _) = input.take_split(offset);
+                return Err(nom::Err::Error(error::Error::new(
+                    rest,
+                    error::ErrorKind::Verify,
+                )));
+            }
+        }
+        offset += len;
+    }
+    let (input, _) = input.take_split(offset);
+    Ok((
+        input,
+        HunkHeader {
+            old_range,
+            new_range,
+            range_hint,
+        },
+    ))
+}
+
+// The span of the input consumed between `start` and `end`
+fn span_between(start: Input<'_>, end: Input<'_>) -> Span {
+    let consumed = &start.fragment()[..end.location_offset() - start.location_offset()];
+    // Don't count the line ending of the last line as the start of another line
+    let last_line = consumed.strip_suffix('\n').unwrap_or(consumed);
+    Span {
+        start: start.location_offset(),
+        end: end.location_offset(),
+        start_line: start.location_line(),
+        end_line: start.location_line() + last_line.matches('\n').count() as u32,
+    }
+}
+
+// Diff tools use the same line ending for every line they write, so take it from the first line
+fn first_line_ending(input: Input<'_>) -> LineEnding {
+    let s = input.fragment();
+    match s.find('\n') {
+        Some(i) if s[..i].ends_with('\r') => LineEnding::CrLf,
+        _ => LineEnding::Lf,
+    }
+}
+
+// Lines after the last hunk of a patch that look like more of its lines, which are read as the
+// preamble of the next patch. Usually the hunk's ranges are miscounted.
+fn extra_hunk_lines(input: Input<'_>) -> IResult<Input<'_>, usize> {
+    many0_count(preceded(
+        tuple((not(tag("--- ")), peek(one_of("+- \\")))),
+        consume_content_line,
+    ))(input)
+}
+
+fn preamble(input: Input<'_>) -> IResult<Input<'_>, usize> {
+    many0_count(preceded(not(preamble_end), consume_content_line))(input)
+}
+
+fn preamble_end(input: Input<'_>) -> IResult<Input<'_>, Input<'_>> {
+    // Most preamble lines can't start a patch, so check their first char before trying each kind
+    // of header
+    let (input, _) = peek(one_of("-dBI="))(input)?;
+    alt((
+        tag("--- "),
+        tag("diff --git "),
+        tag("Binary files "),
+        recognize(index_header),
+        recognize(perforce_header),
+    ))(input)
+}
+
+// Header lines
+fn headers(input: Input<'_>, escapes: EscapeSet) -> IResult<Input<'_>, (File<'_>, File<'_>)> {
+    let (input, _) = tag("--- ")(input)?;
+    let (input, oldfile) = header_line_content(input, escapes)?;
+    let (input, _) = line_ending(input)?;
+    let (input, _) = tag("+++ ")(input)?;
+    let (input, newfile) = header_line_content(input, escapes)?;
+    let (input, _) = line_ending(input)?;
+    Ok((input, (oldfile, newfile)))
+}
+
+// The `Index:` line and separator that `svn diff` and `cvs diff` write before each file
+fn index_header(input: Input<'_>) -> IResult<Input<'_>, Cow<'_, str>> {
+    let (input, path) = delimited(tag("Index: "), not_line_ending, line_ending)(input)?;
+    let (input, _) = terminated(is_a("="), line_ending)(input)?;
+    Ok((input, Cow::Borrowed(*path.fragment())))
+}
+
+// The lines `cvs diff` writes after the `Index:` line, about the revisions it compares
+fn cvs_header<'a>(input: Input<'a>, path: Cow<'a, str>) -> IResult<Input<'a>, CvsMetadata<'a>> {
+    let (input, rcs_file) = delimited(tag("RCS file: "), not_line_ending, line_ending)(input)?;
+    let (input, revisions) = many0(delimited(
+        tag("retrieving revision "),
+        not_line_ending,
+        line_ending,
+    ))(input)?;
+    let (input, diff_command) = opt(terminated(
+        recognize(preceded(tag("diff "), not_line_ending)),
+        line_ending,
+    ))(input)?;
+    Ok((
+        input,
+        CvsMetadata {
+            path,
+            rcs_file: rcs_file.fragment(),
+            revisions: revisions
+                .iter()
+                .map(|revision| *revision.fragment())
+                .collect(),
+            diff_command: diff_command.map(|command| *command.fragment()),
+        },
+    ))
+}
+
+// The line `p4 diff` and `p4 describe` write before each file, like
+// `==== //depot/main/foo.c#3 - /home/me/main/foo.c ====`
+fn perforce_header(input: Input<'_>) -> IResult<Input<'_>, PerforceMetadata<'_>> {
+    let (input, line) = delimited(tag("==== "), not_line_ending, line_ending)(input)?;
+    let line: &str = line.fragment();
+    let header = line.strip_suffix(" ====").and_then(|files| {
+        // Depot paths can't contain `#`, which starts their revision, so the separator between
+        // the paths is the first ` - ` after it
+        let separator = files.find('#').and_then(|hash| {
+            let separator = files[hash..].find(" - ")?;
+            Some(hash + separator)
+        });
+        let (old, new) = match separator {
+            Some(separator) => (&files[..separator], Some(&files[separator + 3..])),
+            None => (files, None),
+        };
+        let (old_path, old_revision, old_file_type) = perforce_file(old);
+        let new = new.map(perforce_file);
+        Some(PerforceMetadata {
+            old_path: Cow::Borrowed(old_path),
+            old_revision: Some(old_revision?),
+            old_file_type,
+            new_path: new.map(|(path, _, _)| Cow::Borrowed(path)),
+            new_revision: new.and_then(|(_, revision, _)| revision),
+            new_file_type: new.and_then(|(_, _, file_type)| file_type),
+        })
+    });
+    match header {
+        Some(header) => Ok((input, header)),
+        None => Err(nom::Err::Error(error::Error::new(
+            input,
+            error::ErrorKind::Verify,
+        ))),
+    }
+}
+
+// Splits a path like `//depot/main/foo.c#3 (text)` into its path, revision and file type
+fn perforce_file(s: &str) -> (&str, Option<&str>, Option<&str>) {
+    let (s, file_type) = match s.strip_suffix(')').and_then(|s| s.rsplit_once(" (")) {
+        Some((s, file_type)) => (s, Some(file_type)),
+        None => (s, None),
+    };
+    match s.rsplit_once('#') {
+        Some((path, revision))
+            if !revision.is_empty() && revision.bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            (path, Some(revision), file_type)
+        }
+        _ => (s, None, file_type),
+    }
+}
+
+fn perforce_files<'a>(perforce: &PerforceMetadata<'a>) -> (File<'a>, File<'a>) {
+    let file = |path: &Cow<'a, str>| File {
+        path: path.clone(),
+        escaped: false,
+        meta: None,
+    };
+    let new_path = perforce.new_path.as_ref().unwrap_or(&perforce.old_path);
+    (file(&perforce.old_path), file(new_path))
+}
+
+// The changes to a file's properties, which `svn diff` writes after its hunks
+fn svn_property_changes(input: Input<'_>) -> IResult<Input<'_>, Vec<PropertyChange<'_>>> {
+    let (input, _) = many0(line_ending)(input)?;
+    let (input, _) = tuple((tag("Property changes on: "), not_line_ending, line_ending))(input)?;
+    let (input, _) = terminated(is_a("_"), line_ending)(input)?;
+    many1(property_change)(input)
+}
+
+fn property_change(input: Input<'_>) -> IResult<Input<'_>, PropertyChange<'_>> {
+    let (input, kind) = alt((
+        value(PropertyChangeKind::Added, tag("Added: ")),
+        value(PropertyChangeKind::Modified, tag("Modified: ")),
+        value(PropertyChangeKind::Deleted, tag("Deleted: ")),
+    ))(input)?;
+    let (input, name) = terminated(not_line_ending, line_ending)(input)?;
+    let (input, (hunk, old_end_newline, new_end_newline)) =
+        delimited_chunk(input, &ParseContext::default(), "##")?;
+    Ok((
+        input,
+        PropertyChange {
+            name: name.fragment(),
+            kind,
+            hunk,
+            old_end_newline,
+            new_end_newline,
+        },
+    ))
+}
+
+// Extended header lines written by `git diff`
+fn git_header(input: Input<'_>) -> IResult<Input<'_>, GitMetadata<'_>> {
+    let (input, _) = tag("diff --git ")(input)?;
+    let (input, (paths, (old_path, new_path))) = consumed(git_diff_paths)(input)?;
+    let escaped_paths = escapes_non_ascii(paths.fragment(), &old_path)
+        || escapes_non_ascii(paths.fragment(), &new_path);
+    let (input, _) = line_ending(input)?;
+    let (input, lines) = many0(git_header_line)(input)?;
+
+    let mut git = GitMetadata {
+        old_path,
+        new_path,
+        escaped_paths,
+        old_hash: None,
+        new_hash: None,
+        old_mode: None,
+        new_mode: None,
+        kind: FileChangeKind::Modified,
+        similarity: None,
+        dissimilarity: None,
+        operation: None,
+    };
+    let mut rename_from = None;
+    let mut copy_from = None;
+    for line in lines {
+        match line {
+            GitHeaderLine::OldMode(mode) => git.old_mode = Some(mode),
+            GitHeaderLine::NewMode(mode) => git.new_mode = Some(mode),
+            GitHeaderLine::NewFileMode(mode) => {
+                git.kind = FileChangeKind::Created;
+                git.new_mode = Some(mode);
+            }
+            GitHeaderLine::DeletedFileMode(mode) => {
+                git.kind = FileChangeKind::Deleted;
+                git.old_mode = Some(mode);
+            }
+            GitHeaderLine::Index(old_hash, new_hash, mode) => {
+                git.old_hash = Some(old_hash);
+                git.new_hash = Some(new_hash);
+                if let Some(mode) = mode {
+                    git.old_mode = git.old_mode.or(Some(mode));
+                    git.new_mode = git.new_mode.or(Some(mode));
+                }
+            }
+            GitHeaderLine::Similarity(percent) => git.similarity = Some(percent),
+            GitHeaderLine::Dissimilarity(percent) => git.dissimilarity = Some(percent),
+            GitHeaderLine::RenameFrom(path) => rename_from = Some(path),
+            GitHeaderLine::RenameTo((to, to_escaped)) => {
+                if let Some((from, from_escaped)) = rename_from.take() {
+                    let escaped = from_escaped || to_escaped;
+                    git.operation = Some(FileOperation::Renamed { from, to, escaped });
+                }
+            }
+            GitHeaderLine::CopyFrom(path) => copy_from = Some(path),
+            GitHeaderLine::CopyTo((to, to_escaped)) => {
+                if let Some((from, from_escaped)) = copy_from.take() {
+                    let escaped = from_escaped || to_escaped;
+                    git.operation = Some(FileOperation::Copied { from, to, escaped });
+                }
+            }
+            GitHeaderLine::Other => {}
+        }
+    }
+    Ok((input, git))
+}
+
+enum GitHeaderLine<'a> {
+    OldMode(u32),
+    NewMode(u32),
+    NewFileMode(u32),
+    DeletedFileMode(u32),
+    Index(&'a str, &'a str, Option<u32>),
+    Similarity(u8),
+    Dissimilarity(u8),
+    RenameFrom((Cow<'a, str>, bool)),
+    RenameTo((Cow<'a, str>, bool)),
+    CopyFrom((Cow<'a, str>, bool)),
+    CopyTo((Cow<'a, str>, bool)),
+    Other,
+}
+
+fn git_header_line(input: Input<'_>) -> IResult<Input<'_>, GitHeaderLine<'_>> {
+    let path = |input| escaped_filename(input, EscapeSet::Git);
+    alt((
+        map(
+            delimited(tag("old mode "), file_mode, line_ending),
+            GitHeaderLine::OldMode,
+        ),
+        map(
+            delimited(tag("new mode "), file_mode, line_ending),
+            GitHeaderLine::NewMode,
+        ),
+        map(
+            delimited(tag("new file mode "), file_mode, line_ending),
+            GitHeaderLine::NewFileMode,
+        ),
+        map(
+            delimited(tag("deleted file mode "), file_mode, line_ending),
+            GitHeaderLine::DeletedFileMode,
+        ),
+        map(
+            delimited(
+                tag("index "),
+                tuple((
+                    hex_digit1,
+                    tag(".."),
+                    hex_digit1,
+                    opt(preceded(char(' '), file_mode)),
+                )),
+                line_ending,
+            ),
+            |(old, _, new, mode): (Input<'_>, _, Input<'_>, _)| {
+                GitHeaderLine::Index(old.fragment(), new.fragment(), mode)
+            },
+        ),
+        map(
+            delimited(tag("similarity index "), percentage, line_ending),
+            GitHeaderLine::Similarity,
+        ),
+        map(
+            delimited(tag("dissimilarity index "), percentage, line_ending),
+            GitHeaderLine::Dissimilarity,
+        ),
+        map(
+            delimited(tag("rename from "), path, line_ending),
+            GitHeaderLine::RenameFrom,
+        ),
+        map(
+            delimited(tag("rename to "), path, line_ending),
+            GitHeaderLine::RenameTo,
+        ),
+        map(
+            delimited(tag("copy from "), path, line_ending),
+            GitHeaderLine::CopyFrom,
+        ),
+        map(
+            delimited(tag("copy to "), path, line_ending),
+            GitHeaderLine::CopyTo,
+        ),
+        // Ignore extended header lines with values that aren't understood, and the `rename old`
+        // and `rename new` lines of old versions of git. Other lines end the header, since in
+        // `git log -p` output they are the next commit.
+        map(
+            preceded(
+                alt((
+                    tag("old mode "),
+                    tag("new mode "),
+                    tag("new file mode "),
+                    tag("deleted file mode "),
+                    tag("index "),
+                    tag("similarity index "),
+                    tag("dissimilarity index "),
+                    tag("rename "),
+                    tag("copy "),
+                )),
+                consume_content_line,
+            ),
+            |_| GitHeaderLine::Other,
+        ),
+    ))(input)
+}
+
+// The line git and GNU diff write instead of hunks for binary files, e.g.
+// `Binary files a/image.png and b/image.png differ`
+fn binary_files_differ(
+    input: Input<'_>,
+    escapes: EscapeSet,
+) -> IResult<Input<'_>, (File<'_>, File<'_>)> {
+    files_differ_line(input, "Binary files ", escapes)
+}
+
+// The line `diff -q` writes for files it doesn't compare, e.g.
+// `Files old/notes.txt and new/notes.txt differ`
+fn files_differ(input: Input<'_>) -> IResult<Input<'_>, (File<'_>, File<'_>)> {
+    files_differ_line(input, "Files ", EscapeSet::Git)
+}
+
+fn files_differ_line<'a>(
+    input: Input<'a>,
+    prefix: &'static str,
+    escapes: EscapeSet,
+) -> IResult<Input<'a>, (File<'a>, File<'a>)> {
+    let (rest, line) = delimited(tag(prefix), not_line_ending, opt(line_ending))(input)?;
+    let line: &str = line.fragment();
+    let paths = line.strip_suffix(" differ").and_then(|paths| {
+        // Like `diff --git` lines, prefer the split where both paths are the same length
+        let mid = paths.len().saturating_sub(5) / 2;
+        let i = match paths.get(mid..) {
+            Some(rest) if rest.starts_with(" and ") => mid,
+            _ => paths.find(" and ")?,
+        };
+        Some((&paths[..i], &paths[i + 5..]))
+    });
+    let (old, new) = match paths {
+        Some(paths) => paths,
+        None => {
+            return Err(nom::Err::Error(error::Error::new(
+                input,
+                error::ErrorKind::Tag,
+            )))
+        }
+    };
+
+    let file = |path: &'a str| {
+        let unquoted = maybe_quoted(path, escapes);
+        File {
+            escaped: escapes_non_ascii(path, &unquoted),
+            path: unquoted,
+            meta: None,
+        }
+    };
+    Ok((rest, (file(old), file(new))))
+}
+
+// The line `diff -r` writes for a file or directory that only one side has, e.g.
+// `Only in new/docs: guide.txt`
+fn only_in(input: Input<'_>) -> IResult<Input<'_>, (&str, &str)> {
+    map_opt(
+        delimited(tag("Only in "), not_line_ending, opt(line_ending)),
+        |line: Input<'_>| line.fragment().split_once(": "),
+    )(input)
+}
+
+// Binary data written by `git diff --binary`
+fn binary_patch(input: Input<'_>) -> IResult<Input<'_>, BinaryPatch> {
+    let (input, _) = terminated(tag("GIT binary patch"), line_ending)(input)?;
+    let (input, forward) = binary_hunk(input)?;
+    let (input, reverse) = opt(binary_hunk)(input)?;
+    Ok((input, BinaryPatch::Git { forward, reverse }))
+}
+
+fn binary_hunk(input: Input<'_>) -> IResult<Input<'_>, BinaryHunk> {
+    let (input, kind) = alt((
+        value(BinaryHunkKind::Literal, tag("literal ")),
+        value(BinaryHunkKind::Delta, tag("delta ")),
+    ))(input)?;
+    let (input, size) = terminated(u64_digit, line_ending)(input)?;
+    let (input, data) = fold_many1(
+        map_opt(consume_content_line, base85::decode_line),
+        Vec::new,
+        |mut data, line| {
+            data.extend(line);
+            data
+        },
+    )(input)?;
+    // Each hunk is terminated by an empty line
+    let (input, _) = opt(line_ending)(input)?;
+    Ok((input, BinaryHunk { kind, size, data }))
 }
 
-// Header lines
-fn headers(input: Input<'_>) -> IResult<Input<'_>, (File, File)> {
-    // Ignore any preamble lines in produced diffs
-    let (input, _) = take_until("---")(input)?;
-    let (input, _) = tag("--- ")(input)?;
-    let (input, oldfile) = header_line_content(input)?;
-    let (input, _) = line_ending(input)?;
-    let (input, _) = tag("+++ ")(input)?;
-    let (input, newfile) = header_line_content(input)?;
-    let (input, _) = line_ending(input)?;
-    Ok((input, (oldfile, newfile)))
+fn percentage(input: Input<'_>) -> IResult<Input<'_>, u8> {
+    terminated(
+        map_res(digit1, |digits: Input<'_>| digits.fragment().parse::<u8>()),
+        char('%'),
+    )(input)
+}
+
+fn file_mode(input: Input<'_>) -> IResult<Input<'_>, u32> {
+    map_res(oct_digit1, |digits: Input<'_>| {
+        u32::from_str_radix(digits.fragment(), 8)
+    })(input)
 }
 
-fn header_line_content(input: Input<'_>) -> IResult<Input<'_>, File> {
-    let (input, filename) = filename(input)?;
-    let (input, after) = opt(preceded(char('\t'), file_metadata))(input)?;
+// The paths on a `diff --git` line are only quoted if they contain special characters, so
+// unquoted paths containing spaces are ambiguous. Like git, prefer the split where both paths are
+// the same after their `a/` and `b/` prefixes, then the split before `b/`.
+fn git_diff_paths(input: Input<'_>) -> IResult<Input<'_>, (Cow<'_, str>, Cow<'_, str>)> {
+    let quoted = |input| quoted(input, EscapeSet::Git);
+    let filename = |input| filename(input, EscapeSet::Git);
+    if let Ok((input, paths)) = separated_pair(quoted, char(' '), filename)(input) {
+        return Ok((input, paths));
+    }
+    let (input, line) = not_line_ending(input)?;
+    let line: &str = line.fragment();
+    if let Some(i) = line.strip_suffix('"').and_then(|_| line.find(" \"")) {
+        if let Ok((_, new)) = quoted(Input::new(&line[i + 1..])) {
+            return Ok((input, (Cow::Borrowed(&line[..i]), new)));
+        }
+    }
+
+    fn without_prefix(path: &str) -> &str {
+        path.split_once('/').map_or("", |(_, rest)| rest)
+    }
+    // Paths with prefixes of the same length are split by the space in the middle of the line.
+    // Only checking that one split keeps this linear for lines with many spaces.
+    let mid = line.len() / 2;
+    let same_path = line.len() % 2 == 1
+        && line.is_char_boundary(mid)
+        && line[mid..].starts_with(' ')
+        && without_prefix(&line[..mid]) == without_prefix(&line[mid + 1..]);
+    let (old, new) = if same_path {
+        (&line[..mid], &line[mid + 1..])
+    } else if let Some(i) = line.find(" b/").or_else(|| line.find(' ')) {
+        (&line[..i], &line[i + 1..])
+    } else {
+        (line, "")
+    };
+    Ok((input, (Cow::Borrowed(old), Cow::Borrowed(new))))
+}
+
+fn header_line_content(input: Input<'_>, escapes: EscapeSet) -> IResult<Input<'_>, File<'_>> {
+    let (input, (filename, escaped)) = escaped_filename(input, escapes)?;
+    let (input, after) = opt(preceded(char('\t'), |input| file_metadata(input, escapes)))(input)?;
 
     Ok((
         input,
         File {
             path: filename,
+            escaped,
             meta: after.and_then(|after| match after {
                 Cow::Borrowed("") => None,
                 Cow::Borrowed("\t") => None,
@@ -149,32 +1563,185 @@ fn header_line_content(input: Input<'_>) -> IResult<Input<'_>, File> {
 }
 
 // Hunks of the file differences
-fn chunks(input: Input<'_>) -> IResult<Input<'_>, Vec<Hunk>> {
-    many1(chunk)(input)
+// Each hunk and whether its old and new sides end in a newline
+fn chunks<'a>(
+    input: Input<'a>,
+    cx: &ParseContext,
+) -> IResult<Input<'a>, (Vec<Hunk<'a>>, bool, bool)> {
+    let parsed = Cell::new(0);
+    let (input, hunks) = fold_many1(
+        |input| {
+            let hunk = chunk_with_end_newlines(input, cx)?;
+            parsed.set(parsed.get() + 1);
+            if cx.options.max_hunks.is_some_and(|max| parsed.get() > max) {
+                return cx.exceeded(LimitExceeded::Hunks, input);
+            }
+            Ok(hunk)
+        },
+        || (Vec::new(), true, true),
+        |(mut hunks, old_acc, new_acc), (hunk, old_end_newline, new_end_newline)| {
+            hunks.push(hunk);
+            (
+                hunks,
+                old_acc && old_end_newline,
+                new_acc && new_end_newline,
+            )
+        },
+    )(input)?;
+    Ok((input, hunks))
+}
+
+fn chunk(input: Input<'_>) -> IResult<Input<'_>, Hunk<'_>> {
+    map(
+        |input| chunk_with_end_newlines(input, &ParseContext::default()),
+        |(hunk, _, _)| hunk,
+    )(input)
+}
+
+fn chunk_with_end_newlines<'a>(
+    input: Input<'a>,
+    cx: &ParseContext,
+) -> IResult<Input<'a>, (Hunk<'a>, bool, bool)> {
+    delimited_chunk(input, cx, "@@")
 }
 
-fn chunk(input: Input<'_>) -> IResult<Input<'_>, Hunk> {
-    let (input, ranges) = chunk_header(input)?;
-    let (input, lines) = many1(chunk_line)(input)?;
+fn delimited_chunk<'a>(
+    input: Input<'a>,
+    cx: &ParseContext,
+    delimiter: &'static str,
+) -> IResult<Input<'a>, (Hunk<'a>, bool, bool)> {
+    let options = &cx.options;
+    let start = input;
+    let ending = first_line_ending(input);
+    let (mut input, (old_range, new_range, range_hint)) = delimited_chunk_header(input, delimiter)?;
+    let mut warnings = Vec::new();
+    if !range_hint.is_empty() && !range_hint.starts_with(' ') {
+        warnings.push(warning(WarningKind::TextAfterHunkHeader, start, input));
+    }
+
+    // Read exactly as many lines as the ranges say, so that content lines like `--- a;` can't be
+    // mistaken for the headers of the next patch
+    let (mut old_lines, mut new_lines) = (old_range.count, new_range.count);
+    let (mut old_end_newline, mut new_end_newline) = (true, true);
+    let mut lines = Vec::new();
+    let mut overflowed = false;
+    loop {
+        let counted = old_lines > 0 || new_lines > 0;
+        let line = if options.strict_counts {
+            if !counted {
+                break;
+            }
+            chunk_line(input, old_lines, new_lines, ending)
+        } else {
+            // Follow the counts while the lines fit them, like with strict counts, so that lines
+            // like `--- a;` aren't taken for headers. Past that, the hunk ends at the next header
+            // or a line that isn't a hunk line. An empty line is only context while the ranges
+            // say there are lines left.
+            match chunk_line(input, old_lines, new_lines, ending) {
+                Ok(line) => Ok(line),
+                _ if chunk_end(input).is_ok()
+                    || (!counted && line_ending::<_, ()>(input).is_ok()) =>
+                {
+                    break
+                }
+                _ => match chunk_line(input, 1, 1, ending) {
+                    Err(nom::Err::Error(_)) => break,
+                    line => line,
+                },
+            }
+        };
+        let (rest, line) = line?;
+        if options.max_hunk_lines.is_some_and(|max| lines.len() >= max) {
+            return cx.exceeded(LimitExceeded::HunkLines, input);
+        }
+        let (Line::Add(text) | Line::Remove(text) | Line::Context(text)) = line;
+        if options.max_line_len.is_some_and(|max| text.len() > max) {
+            return cx.exceeded(LimitExceeded::LineLength, input);
+        }
+        overflowed |= !counted;
+        if line == Line::Context("") && !input.fragment().starts_with(' ') {
+            warnings.push(warning(WarningKind::EmptyContextLine, input, rest));
+        }
+        match line {
+            Line::Add(_) => new_lines = new_lines.saturating_sub(1),
+            Line::Remove(_) => old_lines = old_lines.saturating_sub(1),
+            Line::Context(_) => {
+                old_lines = old_lines.saturating_sub(1);
+                new_lines = new_lines.saturating_sub(1);
+            }
+        }
+
+        // `\ No newline at end of file` can only follow the last line of either side, e.g. the
+        // last removed line before the added lines
+        let (rest, no_newline) = if options.strict_no_newline_indicator {
+            english_no_newline_indicator(rest)?
+        } else {
+            no_newline_indicator(rest)?
+        };
+        if no_newline {
+            let old_last = old_lines == 0 || !options.strict_counts;
+            let new_last = new_lines == 0 || !options.strict_counts;
+            match line {
+                Line::Add(_) if new_last => new_end_newline = false,
+                Line::Remove(_) if old_last => old_end_newline = false,
+                Line::Context(_) if old_last && new_last => {
+                    old_end_newline = false;
+                    new_end_newline = false;
+                }
+                _ => {
+                    return Err(nom::Err::Error(error::Error::new(
+                        rest,
+                        error::ErrorKind::Verify,
+                    )))
+                }
+            }
+        }
+        lines.push(line);
+        input = rest;
+    }
+    if overflowed || old_lines > 0 || new_lines > 0 {
+        warnings.push(warning(WarningKind::HunkLineCount, start, input));
+    }
+    cx.warnings.borrow_mut().extend(warnings);
 
-    let (old_range, new_range, range_hint) = ranges;
     Ok((
         input,
-        Hunk {
-            old_range,
-            new_range,
-            range_hint,
-            lines,
-        },
+        (
+            Hunk {
+                old_range,
+                new_range,
+                range_hint,
+                lines,
+            },
+            old_end_newline,
+            new_end_newline,
+        ),
     ))
 }
 
+// The start of the next hunk or patch
+fn chunk_end(input: Input<'_>) -> IResult<Input<'_>, Input<'_>> {
+    alt((
+        tag("@@ "),
+        tag("diff --git "),
+        recognize(|input| headers(input, EscapeSet::Git)),
+    ))(input)
+}
+
 fn chunk_header(input: Input<'_>) -> IResult<Input<'_>, (Range, Range, &'_ str)> {
-    let (input, _) = tag("@@ -")(input)?;
+    delimited_chunk_header(input, "@@")
+}
+
+// A hunk header with the given delimiter, which is `##` for property changes from `svn diff`
+fn delimited_chunk_header<'a>(
+    input: Input<'a>,
+    delimiter: &'static str,
+) -> IResult<Input<'a>, (Range, Range, &'a str)> {
+    let (input, _) = terminated(tag(delimiter), tag(" -"))(input)?;
     let (input, old_range) = range(input)?;
     let (input, _) = tag(" +")(input)?;
     let (input, new_range) = range(input)?;
-    let (input, _) = tag(" @@")(input)?;
+    let (input, _) = preceded(char(' '), tag(delimiter))(input)?;
 
     // Save hint provided after @@ (git sometimes adds this)
     let (input, range_hint) = not_line_ending(input)?;
@@ -189,109 +1756,715 @@ fn range(input: Input<'_>) -> IResult<Input<'_>, Range> {
     Ok((input, Range { start, count }))
 }
 
+// A number that fits in a u64, so that untrusted input with a huge number is an error
 fn u64_digit(input: Input<'_>) -> IResult<Input<'_>, u64> {
-    let (input, digits) = digit1(input)?;
-    let num = digits.fragment().parse::<u64>().unwrap();
-    Ok((input, num))
-}
-
-// Looks for lines starting with + or - or space, but not +++ or ---. Not a foolproof check.
-//
-// For example, if someone deletes a line that was using the pre-decrement (--) operator or adds a
-// line that was using the pre-increment (++) operator, this will fail.
-//
-// Example where this doesn't work:
-//
-// --- main.c
-// +++ main.c
-// @@ -1,4 +1,7 @@
-// +#include<stdio.h>
-// +
-//  int main() {
-//  double a;
-// --- a;
-// +++ a;
-// +printf("%d\n", a);
-//  }
-//
-// We will fail to parse this entire diff.
-//
-// By checking for `+++ ` instead of just `+++`, we add at least a little more robustness because
-// we know that people typically write `++a`, not `++ a`. That being said, this is still not enough
-// to guarantee correctness in all cases.
-//
-//FIXME: Use the ranges in the chunk header to figure out how many chunk lines to parse. Will need
-// to figure out how to count in nom more robustly than many1!(). Maybe using switch!()?
-//FIXME: The test_parse_triple_plus_minus_hack test will no longer panic when this is fixed.
-fn chunk_line(input: Input<'_>) -> IResult<Input<'_>, Line> {
+    map_res(digit1, |digits: Input<'_>| digits.fragment().parse::<u64>())(input)
+}
+
+// A line starting with `+`, `-` or a space, as long as the hunk has lines left on that side
+fn chunk_line(
+    input: Input<'_>,
+    old_lines: u64,
+    new_lines: u64,
+    ending: LineEnding,
+) -> IResult<Input<'_>, Line<'_>> {
+    let text = |input| chunk_line_text(input, ending);
     alt((
         map(
-            preceded(tuple((char('+'), not(tag("++ ")))), consume_content_line),
+            preceded(verify(char('+'), |_| new_lines > 0), text),
             Line::Add,
         ),
         map(
-            preceded(tuple((char('-'), not(tag("-- ")))), consume_content_line),
+            preceded(verify(char('-'), |_| old_lines > 0), text),
             Line::Remove,
         ),
-        map(preceded(char(' '), consume_content_line), Line::Context),
+        map(
+            preceded(verify(char(' '), |_| old_lines > 0 && new_lines > 0), text),
+            Line::Context,
+        ),
+        // Like GNU patch, accept an empty context line, whose space was stripped as trailing
+        // whitespace (e.g. by an editor or email client)
+        map(
+            verify(line_ending, |_| old_lines > 0 && new_lines > 0),
+            |_| Line::Context(""),
+        ),
     ))(input)
 }
 
-// Trailing newline indicator
+// The text of a hunk line, which only ends at `\n` (or the end of the input). Only the patch's
+// own line ending is removed, so a `\r` from a file with mixed line endings stays in the text.
+fn chunk_line_text(input: Input<'_>, ending: LineEnding) -> IResult<Input<'_>, &str> {
+    let end = memchr(b'\n', input.fragment().as_bytes()).unwrap_or(input.fragment().len());
+    let (input, text) = input.take_split(end);
+    let (input, _) = alt((tag("\n"), eof))(input)?;
+    let text: &str = text.fragment();
+    let text = match ending {
+        LineEnding::CrLf => text.strip_suffix('\r').unwrap_or(text),
+        LineEnding::Lf => text,
+    };
+    Ok((input, text))
+}
+
+// A change in the default output format of `diff`, like `3,4c3`, followed by the removed lines
+// starting with `<` and the added lines starting with `>`
+fn normal_chunk(input: Input<'_>) -> IResult<Input<'_>, (Hunk<'_>, bool, bool)> {
+    let line_range = |input| {
+        let (input, first) = u64_digit(input)?;
+        let (input, last) = opt(preceded(char(','), u64_digit))(input)?;
+        Ok((input, (first, last.unwrap_or(first))))
+    };
+    // A range like `5,3` can't be counted, and neither can one with too many lines
+    let line_range = |input| {
+        verify(line_range, |&(first, last)| {
+            last.checked_sub(first)
+                .and_then(|n| n.checked_add(1))
+                .is_some()
+        })(input)
+    };
+    let (input, (old, command, new)) =
+        terminated(tuple((line_range, one_of("acd"), line_range)), line_ending)(input)?;
+    // Appends and deletes give the line before the empty side, like an empty unified range
+    let range = |(first, last): (u64, u64), empty| Range {
+        start: first,
+        count: if empty { 0 } else { last - first + 1 },
+    };
+    let old_range = range(old, command == 'a');
+    let new_range = range(new, command == 'd');
+    let (input, (removed, old_end_newline)) = normal_lines(input, '<', old_range.count)?;
+    let (input, _) = cond(command == 'c', terminated(tag("---"), line_ending))(input)?;
+    let (input, (added, new_end_newline)) = normal_lines(input, '>', new_range.count)?;
+
+    let lines = removed
+        .into_iter()
+        .map(Line::Remove)
+        .chain(added.into_iter().map(Line::Add))
+        .collect();
+    let hunk = Hunk {
+        old_range,
+        new_range,
+        range_hint: "",
+        lines,
+    };
+    Ok((input, (hunk, old_end_newline, new_end_newline)))
+}
+
+// The given number of lines starting with `marker`, and whether the last one ends in a newline
+fn normal_lines(input: Input<'_>, marker: char, len: u64) -> IResult<Input<'_>, (Vec<&str>, bool)> {
+    // `diff --suppress-blank-empty` leaves out the space after the marker of empty lines
+    let text = alt((preceded(char(' '), content_line), value("", line_ending)));
+    let (input, lines) = count(preceded(char(marker), text), len as usize)(input)?;
+    let (input, no_newline) = no_newline_indicator(input)?;
+    Ok((input, (lines, !no_newline)))
+}
+
+// Commands of an ed script written by `diff -e`, like `3,5c`
+fn ed_command(input: Input<'_>) -> IResult<Input<'_>, EdCommand<'_>> {
+    let (input, start) = u64_digit(input)?;
+    let (input, end) = opt(preceded(char(','), u64_digit))(input)?;
+    let (input, kind) = terminated(
+        alt((
+            value(EdCommandKind::Append, char('a')),
+            value(EdCommandKind::Change, char('c')),
+            value(EdCommandKind::Delete, char('d')),
+        )),
+        line_ending,
+    )(input)?;
+    let (input, lines) = match kind {
+        EdCommandKind::Delete => (input, Vec::new()),
+        _ => ed_text(input)?,
+    };
+    Ok((
+        input,
+        EdCommand {
+            start,
+            end: end.unwrap_or(start),
+            kind,
+            lines,
+        },
+    ))
+}
+
+// The text of an append or change command, which ends with a line of only `.`
+fn ed_text(mut input: Input<'_>) -> IResult<Input<'_>, Vec<&str>> {
+    let text_end = |input| terminated(char('.'), line_ending)(input);
+    let mut lines = Vec::new();
+    loop {
+        let (rest, block) =
+            terminated(many0(preceded(not(text_end), content_line)), text_end)(input)?;
+        lines.extend(block);
+
+        // GNU diff writes a line of only `.` as `..` and removes the extra `.` after the text
+        // ends, then appends the rest of the text with another `a` command
+        let (rest, fixed) = opt(terminated(tag("s/.//"), line_ending))(rest)?;
+        if fixed.is_none() {
+            return Ok((rest, lines));
+        }
+        if let Some(last) = lines.last_mut() {
+            *last = last.strip_prefix('.').unwrap_or(last);
+        }
+        match opt(terminated(char('a'), line_ending))(rest)? {
+            (rest, Some(_)) => input = rest,
+            (rest, None) => return Ok((rest, lines)),
+        }
+    }
+}
+
+// A line of a quilt series file, which is `None` for empty and comment lines
+fn series_entry(line: Input<'_>) -> Option<Option<SeriesEntry<'_>>> {
+    let line: &str = line.fragment();
+    let line = match line.find('#') {
+        // quilt only reads `#` as a comment at the start of a word
+        Some(i) if i == 0 || line[..i].ends_with(char::is_whitespace) => &line[..i],
+        _ => line,
+    };
+    let mut words = line.split_whitespace();
+    let name = match words.next() {
+        Some(name) => name,
+        None => return Some(None),
+    };
+    let mut entry = SeriesEntry {
+        name,
+        strip: 1,
+        reverse: false,
+    };
+    for word in words {
+        match word.strip_prefix("-p") {
+            Some(strip) => entry.strip = strip.parse().ok()?,
+            None if word == "-R" => entry.reverse = true,
+            None => return None,
+        }
+    }
+    Some(Some(entry))
+}
+
+// Emails written by `git format-patch`
+fn commit_patch(input: Input<'_>) -> IResult<Input<'_>, CommitPatch<'_>> {
+    let start = input;
+    // The mbox line starting each email, e.g. `From <sha> Mon Sep 17 00:00:00 2001`
+    let (input, _) = opt(preceded(tag("From "), consume_content_line))(input)?;
+    let (input, headers) = many0(mail_header)(input)?;
+    let (input, _) = line_ending(input)?;
+    let separator = || terminated(tag("---"), line_ending);
+    let (input, message) = recognize(many0_count(preceded(
+        tuple((not(separator()), not(signature))),
+        consume_content_line,
+    )))(input)?;
+    let (input, _) = opt(separator())(input)?;
+    // The diffstat is skipped like any other preamble
+    let (input, patches) = many0(patch)(input)?;
+    let (input, _) = opt(signature)(input)?;
+    let (input, _) = preamble(input)?;
+    if preamble_end(input).is_ok() {
+        // Parsing stopped at a patch that is invalid, so report why
+        patch(input)?;
+    }
+
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+    };
+    let missing = || nom::Err::Error(error::Error::new(start, error::ErrorKind::Tag));
+    let author = header("From").ok_or_else(missing)?;
+    let subject = header("Subject").ok_or_else(missing)?;
+    let date = header("Date")
+        .and_then(|date| DateTime::parse_from_rfc2822(&date).ok())
+        .ok_or_else(missing)?;
+
+    Ok((
+        input,
+        CommitPatch {
+            author,
+            date,
+            subject: without_subject_prefix(subject),
+            message: message.fragment().trim_end(),
+            patches,
+        },
+    ))
+}
+
+// The signature git adds at the end of an email, a `-- ` line and the version of git. It is only
+// looked for after the patches, since a removed line `- ` looks the same inside a hunk.
+fn signature(input: Input<'_>) -> IResult<Input<'_>, ()> {
+    value(
+        (),
+        tuple((
+            terminated(tag("-- "), line_ending),
+            opt(content_line),
+            many0(line_ending),
+            eof,
+        )),
+    )(input)
+}
+
+// One email of an mbox file, which ends where the next `From ` line starts
+fn mbox_message(input: Input<'_>) -> IResult<Input<'_>, CommitPatch<'_>> {
+    let (input, _) = not(eof)(input)?;
+    let s: &str = input.fragment();
+    let mut end = s.len();
+    let mut offset = 0;
+    for line in s.split_inclusive('\n') {
+        if offset > 0 && is_mbox_from_line(line) {
+            end = offset;
+            break;
+        }
+        offset += line.len();
+    }
+    let (rest, message) = take(end)(input)?;
+    // Don't let a broken message look like the end of the mbox
+    let (_, commit) = cut(all_consuming(commit_patch))(message)?;
+    Ok((rest, commit))
+}
+
+// Lines in the message body starting with `From ` are escaped in mbox files, but check that this
+// looks like `From 961f106701c472f0f3c95609c0b0bf99fb378d98 Mon Sep 17 00:00:00 2001` anyway
+fn is_mbox_from_line(line: &str) -> bool {
+    let line = match line.strip_prefix("From ") {
+        Some(line) => line.trim_end(),
+        None => return false,
+    };
+    let year = line.rsplit(' ').next().unwrap_or_default();
+    year.len() == 4
+        && year.bytes().all(|b| b.is_ascii_digit())
+        && line.split(' ').any(|word| word.matches(':').count() == 2)
+}
+
+// A header line and its continuation lines, which are unfolded into a single line
+fn mail_header(input: Input<'_>) -> IResult<Input<'_>, (&str, Cow<'_, str>)> {
+    let (input, name) = terminated(is_not(": \t\r\n"), char(':'))(input)?;
+    let (input, value) = preceded(opt(char(' ')), consume_content_line)(input)?;
+    let (input, value) = fold_many0(
+        preceded(peek(one_of(" \t")), consume_content_line),
+        move || Cow::Borrowed(value),
+        |mut value: Cow<str>, continuation| {
+            value.to_mut().push_str(continuation);
+            value
+        },
+    )(input)?;
+    Ok((input, (name.fragment(), value)))
+}
+
+// Removes prefixes like `[PATCH v2 1/3]`, like `git am` does
+fn without_subject_prefix(subject: Cow<'_, str>) -> Cow<'_, str> {
+    let mut rest: &str = &subject;
+    while let Some(end) = rest.strip_prefix('[').and_then(|s| s.find(']')) {
+        rest = rest[end + 2..].trim_start();
+    }
+    if rest.len() == subject.len() {
+        return subject;
+    }
+    match subject {
+        Cow::Borrowed(subject) => Cow::Borrowed(&subject[subject.len() - rest.len()..]),
+        Cow::Owned(_) => Cow::Owned(rest.to_owned()),
+    }
+}
+
+// A line of `git diff --numstat`, e.g. `1\t2\tpath`, or `-\t-\tpath` for binary files
+fn numstat_line(input: Input<'_>) -> IResult<Input<'_>, FileStat<'_>> {
+    let counts = alt((
+        map(tag("-\t-"), |_| None),
+        map(separated_pair(u64_digit, char('\t'), u64_digit), Some),
+    ));
+    let (input, (counts, path)) = terminated(
+        separated_pair(counts, char('\t'), |input| filename(input, EscapeSet::Git)),
+        alt((line_ending, eof)),
+    )(input)?;
+    let (additions, deletions) = counts.unwrap_or_default();
+    Ok((
+        input,
+        FileStat {
+            path,
+            additions,
+            deletions,
+            binary: counts.is_none(),
+        },
+    ))
+}
+
+// A line of `git diff --stat` without its leading space, e.g. `path | 2 +-` or
+// `image.png | Bin 0 -> 1234 bytes`
+fn stat_line(line: &str) -> Option<FileStat<'_>> {
+    let (path, stat) = line.rsplit_once(" | ")?;
+    let path = maybe_quoted(path.trim_end(), EscapeSet::Git);
+    let stat = stat.trim();
+    if stat.starts_with("Bin") {
+        return Some(FileStat {
+            path,
+            additions: 0,
+            deletions: 0,
+            binary: true,
+        });
+    }
+
+    let (changes, graph) = stat.split_once(' ').unwrap_or((stat, ""));
+    let changes = changes.parse::<u64>().ok()?;
+    let plus = graph.matches('+').count() as u64;
+    let minus = graph.matches('-').count() as u64;
+    // The graph is scaled down for large changes, so split the total in the same proportions.
+    // Counts too large for that are an error, like other numbers too large to parse.
+    let total = plus + minus;
+    let additions = match total {
+        0 => 0,
+        _ => changes.checked_mul(plus)?.checked_add(total / 2)? / total,
+    };
+    Some(FileStat {
+        path,
+        additions,
+        deletions: changes - additions,
+        binary: false,
+    })
+}
+
+// A commit pair of `git range-diff`, e.g. `1:  6d2af67 ! 1:  56d16ca Change two`, and the diff of
+// their patches if they changed
+fn range_diff_entry(input: Input<'_>) -> IResult<Input<'_>, RangeDiffEntry<'_>> {
+    let (input, old) = range_diff_commit(input)?;
+    let (input, status) = delimited(
+        char(' '),
+        alt((
+            value(RangeDiffStatus::Unchanged, char('=')),
+            value(RangeDiffStatus::Changed, char('!')),
+            value(RangeDiffStatus::Removed, char('<')),
+            value(RangeDiffStatus::Added, char('>')),
+        )),
+        char(' '),
+    )(input)?;
+    let (input, new) = range_diff_commit(input)?;
+    let (input, subject) = preceded(opt(char(' ')), content_line)(input)?;
+    let (input, sections) = many0(range_diff_section)(input)?;
+    Ok((
+        input,
+        RangeDiffEntry {
+            old,
+            new,
+            status,
+            subject,
+            sections,
+        },
+    ))
+}
+
+// A commit number and hash, or dashes if the commit is missing from that series
+fn range_diff_commit(input: Input<'_>) -> IResult<Input<'_>, Option<RangeDiffCommit<'_>>> {
+    let index = map_res(digit1, |digits: Input<'_>| digits.fragment().parse::<u32>());
+    preceded(
+        many0_count(char(' ')),
+        alt((
+            map(tuple((tag("-:  "), is_a("-"))), |_| None),
+            map(
+                separated_pair(index, tag(":  "), hex_digit1),
+                |(index, hash)| {
+                    Some(RangeDiffCommit {
+                        index,
+                        hash: hash.fragment(),
+                    })
+                },
+            ),
+        )),
+    )(input)
+}
+
+// The diff of two patches is indented by four spaces
+fn range_diff_section(input: Input<'_>) -> IResult<Input<'_>, RangeDiffSection<'_>> {
+    let (input, header) = preceded(tag("    @@"), content_line)(input)?;
+    let (input, lines) = many0(preceded(
+        tag("    "),
+        alt((
+            map(preceded(char('+'), content_line), Line::Add),
+            map(preceded(char('-'), content_line), Line::Remove),
+            map(preceded(char(' '), content_line), Line::Context),
+            map(line_ending, |_| Line::Context("")),
+        )),
+    ))(input)?;
+    Ok((input, RangeDiffSection { header, lines }))
+}
+
+// Combined diffs written by git for merge commits
+fn combined_patch(input: Input<'_>) -> IResult<Input<'_>, CombinedPatch<'_>> {
+    let combined_start = || alt((tag("diff --cc "), tag("diff --combined ")));
+    let (input, _) = many0_count(preceded(not(combined_start()), consume_content_line))(input)?;
+    let (input, dense) = map(combined_start(), |start: Input<'_>| {
+        *start.fragment() == "diff --cc "
+    })(input)?;
+    let (input, (path, escaped_path)) =
+        terminated(|input| escaped_filename(input, EscapeSet::Git), line_ending)(input)?;
+    // Keep the `index` line and ignore other extended header lines, like `mode`
+    let (input, index) = fold_many0(
+        preceded(
+            not(tag("--- ")),
+            alt((
+                map(combined_index, Some),
+                map(consume_content_line, |_| None),
+            )),
+        ),
+        || None,
+        |acc, index| index.or(acc),
+    )(input)?;
+    let (input, (old, new)) = headers(input, EscapeSet::Git)?;
+    let (input, hunks) = many1(combined_chunk)(input)?;
+    let (input, no_newline_indicator) = no_newline_indicator(input)?;
+    let (input, _) = many0(line_ending)(input)?;
+
+    let (old_hashes, new_hash) = match index {
+        Some((old_hashes, new_hash)) => (old_hashes, Some(new_hash)),
+        None => (Vec::new(), None),
+    };
+    Ok((
+        input,
+        CombinedPatch {
+            path,
+            escaped_path,
+            dense,
+            old_hashes,
+            new_hash,
+            old,
+            new,
+            hunks,
+            end_newline: !no_newline_indicator,
+        },
+    ))
+}
+
+fn combined_index(input: Input<'_>) -> IResult<Input<'_>, (Vec<&str>, &str)> {
+    let hash = |input| map(hex_digit1, |hash: Input<'_>| *hash.fragment())(input);
+    delimited(
+        tag("index "),
+        separated_pair(separated_list1(char(','), hash), tag(".."), hash),
+        line_ending,
+    )(input)
+}
+
+fn combined_chunk(input: Input<'_>) -> IResult<Input<'_>, CombinedHunk<'_>> {
+    // The header starts with one more `@` than there are parents, and at least two parents
+    let (input, ats) = verify(is_a("@"), |ats: &Input<'_>| ats.fragment().len() >= 3)(input)?;
+    let parents = ats.fragment().len() - 1;
+    let (input, old_ranges) = count(preceded(tag(" -"), range), parents)(input)?;
+    let (input, _) = tag(" +")(input)?;
+    let (input, new_range) = range(input)?;
+    let (input, _) = preceded(char(' '), tag(*ats.fragment()))(input)?;
+    let (input, range_hint) = terminated(not_line_ending, line_ending)(input)?;
+
+    // Unlike regular hunks, the next patch always starts with a `diff` line, so there's no
+    // ambiguity with `---` and `+++` lines here
+    let (input, lines) = many1(|input| combined_line(input, parents))(input)?;
+    Ok((
+        input,
+        CombinedHunk {
+            old_ranges,
+            new_range,
+            range_hint: range_hint.fragment(),
+            lines,
+        },
+    ))
+}
+
+fn combined_line(input: Input<'_>, parents: usize) -> IResult<Input<'_>, CombinedLine<'_>> {
+    let (input, markers) =
+        take_while_m_n(parents, parents, |ch| matches!(ch, ' ' | '+' | '-'))(input)?;
+    let (input, text) = consume_content_line(input)?;
+    Ok((
+        input,
+        CombinedLine {
+            markers: markers.fragment(),
+            text,
+        },
+    ))
+}
+
+// Word diffs written by `git diff --word-diff=plain`
+fn word_diff_patch(input: Input<'_>) -> IResult<Input<'_>, WordDiffPatch<'_>> {
+    let (input, _) = preamble(input)?;
+    let (input, git) = opt(git_header)(input)?;
+    let (input, (old, new, hunks)) = match &git {
+        // git leaves out the file headers and hunks when only metadata changed, e.g. renames
+        Some(git) if !input.fragment().starts_with("--- ") => {
+            let old = File {
+                path: git.old_path.clone(),
+                escaped: git.escaped_paths,
+                meta: None,
+            };
+            let new = File {
+                path: git.new_path.clone(),
+                escaped: git.escaped_paths,
+                meta: None,
+            };
+            // Binary files have no words to diff
+            let (input, _) = opt(|input| binary_files_differ(input, EscapeSet::Git))(input)?;
+            (input, (old, new, Vec::new()))
+        }
+        _ => {
+            let (input, (old, new)) = headers(input, EscapeSet::Git)?;
+            let (input, hunks) = many1(word_diff_chunk)(input)?;
+            (input, (old, new, hunks))
+        }
+    };
+    let (input, _) = many0(line_ending)(input)?;
+    Ok((
+        input,
+        WordDiffPatch {
+            old,
+            new,
+            git,
+            hunks,
+        },
+    ))
+}
+
+fn word_diff_chunk(input: Input<'_>) -> IResult<Input<'_>, WordDiffHunk<'_>> {
+    let (input, (old_range, new_range, range_hint)) = chunk_header(input)?;
+    // Lines have no prefix, and git joins and splits lines around changed words, so the counts in
+    // the header can't tell where the hunk ends. Stop at the next hunk or patch instead.
+    let (input, lines) = many1(preceded(
+        not(alt((tag("@@ -"), tag("diff --git ")))),
+        map(consume_content_line, WordDiffLine::parse),
+    ))(input)?;
+    Ok((
+        input,
+        WordDiffHunk {
+            old_range,
+            new_range,
+            range_hint,
+            lines,
+        },
+    ))
+}
+
+// Trailing newline indicator, i.e. `\ No newline at end of file`. GNU diff translates the text
+// on other locales (e.g. `\ Pas de fin de ligne à la fin du fichier`), so accept any line
+// starting with `\ `, which can't be a hunk line.
 fn no_newline_indicator(input: Input<'_>) -> IResult<Input<'_>, bool> {
+    map(opt(preceded(tag("\\ "), content_line)), |matched| {
+        matched.is_some()
+    })(input)
+}
+
+// Only the English indicator, for `ParserOptions::strict_no_newline_indicator`
+fn english_no_newline_indicator(input: Input<'_>) -> IResult<Input<'_>, bool> {
     map(
         opt(terminated(
             tag("\\ No newline at end of file"),
-            opt(line_ending),
+            alt((line_ending, eof)),
         )),
         |matched| matched.is_some(),
     )(input)
 }
 
-fn filename(input: Input<'_>) -> IResult<Input<'_>, Cow<str>> {
-    alt((quoted, bare))(input)
+fn filename(input: Input<'_>, escapes: EscapeSet) -> IResult<Input<'_>, Cow<'_, str>> {
+    alt((|input| quoted(input, escapes), bare))(input)
+}
+
+// Like `filename`, but also whether the non-ASCII characters of the path were escaped
+fn escaped_filename(
+    input: Input<'_>,
+    escapes: EscapeSet,
+) -> IResult<Input<'_>, (Cow<'_, str>, bool)> {
+    map(consumed(|input| filename(input, escapes)), |(raw, path)| {
+        let escaped = escapes_non_ascii(raw.fragment(), &path);
+        (path, escaped)
+    })(input)
+}
+
+// Whether `path` has non-ASCII characters that were escaped in `raw`, the text it was read from
+fn escapes_non_ascii(raw: &str, path: &str) -> bool {
+    raw.is_ascii() && !path.is_ascii()
 }
 
-fn file_metadata(input: Input<'_>) -> IResult<Input<'_>, Cow<str>> {
+fn file_metadata(input: Input<'_>, escapes: EscapeSet) -> IResult<Input<'_>, Cow<'_, str>> {
     alt((
-        quoted,
+        |input| quoted(input, escapes),
         map(not_line_ending, |data: Input<'_>| {
             Cow::Borrowed(*data.fragment())
         }),
     ))(input)
 }
 
-fn quoted(input: Input<'_>) -> IResult<Input<'_>, Cow<str>> {
-    delimited(char('\"'), unescaped_str, char('\"'))(input)
+fn quoted(input: Input<'_>, escapes: EscapeSet) -> IResult<Input<'_>, Cow<'_, str>> {
+    delimited(
+        char('\"'),
+        |input| unescaped_str(input, escapes),
+        char('\"'),
+    )(input)
+}
+
+// Unquotes the whole path if it is quoted
+fn maybe_quoted(path: &str, escapes: EscapeSet) -> Cow<'_, str> {
+    match quoted(Input::new(path), escapes) {
+        Ok((rest, unquoted)) if rest.fragment().is_empty() => unquoted,
+        _ => Cow::Borrowed(path),
+    }
 }
 
-fn bare(input: Input<'_>) -> IResult<Input<'_>, Cow<str>> {
+fn bare(input: Input<'_>) -> IResult<Input<'_>, Cow<'_, str>> {
     map(is_not("\t\r\n"), |data: Input<'_>| {
         Cow::Borrowed(*data.fragment())
     })(input)
 }
 
-fn unescaped_str(input: Input<'_>) -> IResult<Input<'_>, Cow<str>> {
-    let (input, raw) = many1(alt((unescaped_char, escaped_char)))(input)?;
-    Ok((input, raw.into_iter().collect::<Cow<str>>()))
+// Only allocates if the string contains escaped characters
+fn unescaped_str(input: Input<'_>, escapes: EscapeSet) -> IResult<Input<'_>, Cow<'_, str>> {
+    fold_many1(
+        alt((
+            map(unescaped_chars, Cow::Borrowed),
+            // Consecutive escapes are decoded together, since git escapes each byte of a
+            // non-ASCII character separately. Bytes that aren't UTF-8 become U+FFFD.
+            map(
+                fold_many1(|input| escaped_byte(input, escapes), Vec::new, push_byte),
+                |bytes| Cow::Owned(String::from_utf8_lossy(&bytes).into_owned()),
+            ),
+        )),
+        || Cow::Borrowed(""),
+        |mut acc: Cow<str>, part| {
+            if acc.is_empty() {
+                part
+            } else {
+                acc.to_mut().push_str(&part);
+                acc
+            }
+        },
+    )(input)
+}
+
+fn push_byte(mut bytes: Vec<u8>, byte: u8) -> Vec<u8> {
+    bytes.push(byte);
+    bytes
 }
 
-// Parses an unescaped character
-fn unescaped_char(input: Input<'_>) -> IResult<Input<'_>, char> {
-    none_of("\0\n\r\t\\\"")(input)
+// Parses a run of unescaped characters
+fn unescaped_chars(input: Input<'_>) -> IResult<Input<'_>, &str> {
+    map(is_not("\0\n\r\t\\\""), |data: Input<'_>| *data.fragment())(input)
 }
 
-// Parses an escaped character and returns its unescaped equivalent
-fn escaped_char(input: Input<'_>) -> IResult<Input<'_>, char> {
-    map(preceded(char('\\'), one_of(r#"0nrt"\"#)), |ch| match ch {
-        '0' => '\0',
-        'n' => '\n',
-        'r' => '\r',
-        't' => '\t',
-        '"' => '"',
-        '\\' => '\\',
-        _ => unreachable!(),
-    })(input)
+// Parses an escaped byte, like `\n`, or with the git escapes also the octal and hex escapes `\346`
+// and `\xe6`
+fn escaped_byte(input: Input<'_>, escapes: EscapeSet) -> IResult<Input<'_>, u8> {
+    let git = escapes == EscapeSet::Git;
+    let digits = |count, radix| {
+        map_res(
+            take_while_m_n(count, count, move |ch: char| ch.is_digit(radix)),
+            move |digits: Input<'_>| u8::from_str_radix(digits.fragment(), radix),
+        )
+    };
+    preceded(
+        char('\\'),
+        alt((
+            map_opt(cond(git, digits(3, 8)), |byte| byte),
+            map_opt(cond(git, preceded(char('x'), digits(2, 16))), |byte| byte),
+            map_opt(one_of(r#"0abtnvfr"\"#), move |ch| match ch {
+                '0' => Some(b'\0'),
+                't' => Some(b'\t'),
+                'n' => Some(b'\n'),
+                'r' => Some(b'\r'),
+                '"' => Some(b'"'),
+                '\\' => Some(b'\\'),
+                'a' if git => Some(0x07),
+                'b' if git => Some(0x08),
+                'v' if git => Some(0x0b),
+                'f' if git => Some(0x0c),
+                _ => None,
+            }),
+        )),
+    )(input)
 }
 
 #[cfg(test)]
@@ -304,26 +2477,33 @@ mod tests {
 
     // Using a macro instead of a function so that error messages cite the most helpful line number
     macro_rules! test_parser {
-        ($parser:ident($input:expr) -> @($expected_remaining_input:expr, $expected:expr $(,)*)) => {
-            let (remaining_input, result) = $parser(Input::new($input))?;
+        ($parser:ident($input:expr $(, $arg:expr)*) -> @($expected_remaining_input:expr, $expected:expr $(,)*)) => {
+            let (remaining_input, result) = $parser(Input::new($input) $(, $arg)*)?;
             assert_eq!(*remaining_input.fragment(), $expected_remaining_input,
                 "unexpected remaining input after parse");
             assert_eq!(result, $expected);
         };
-        ($parser:ident($input:expr) -> $expected:expr) => {
-            test_parser!($parser($input) -> @("", $expected));
+        ($parser:ident($input:expr $(, $arg:expr)*) -> $expected:expr) => {
+            test_parser!($parser($input $(, $arg)*) -> @("", $expected));
         };
     }
 
     #[test]
     fn test_unescape() -> ParseResult<'static, ()> {
-        test_parser!(unescaped_str("file \\\"name\\\"") -> "file \"name\"".to_string());
+        test_parser!(unescaped_str("file \\\"name\\\"", EscapeSet::Git) -> "file \"name\"".to_string());
+        test_parser!(unescaped_str("\\346\\226\\260.txt", EscapeSet::Git) -> "新.txt".to_string());
+        test_parser!(unescaped_str("\\xe6\\x96\\xb0\\a\\0", EscapeSet::Git) -> "新\x07\0".to_string());
+        test_parser!(unescaped_str("\\377", EscapeSet::Git) -> "\u{fffd}".to_string());
+        test_parser!(unescaped_str("tab\\there", EscapeSet::C) -> "tab\there".to_string());
+        assert!(unescaped_str(Input::new("\\346"), EscapeSet::C).is_err());
+        assert!(unescaped_str(Input::new("\\xe6"), EscapeSet::C).is_err());
+        assert!(unescaped_str(Input::new("\\a"), EscapeSet::C).is_err());
         Ok(())
     }
 
     #[test]
     fn test_quoted() -> ParseResult<'static, ()> {
-        test_parser!(quoted("\"file name\"") -> "file name".to_string());
+        test_parser!(quoted("\"file name\"", EscapeSet::Git) -> "file name".to_string());
         Ok(())
     }
 
@@ -338,46 +2518,50 @@ mod tests {
     #[test]
     fn test_filename() -> ParseResult<'static, ()> {
         // bare
-        test_parser!(filename("asdf\t") -> @("\t", "asdf".to_string()));
+        test_parser!(filename("asdf\t", EscapeSet::Git) -> @("\t", "asdf".to_string()));
 
         // quoted
-        test_parser!(filename(r#""a/My Project/src/foo.rs" "#) -> @(" ", "a/My Project/src/foo.rs".to_string()));
-        test_parser!(filename(r#""\"asdf\" fdsh \\\t\r" "#) -> @(" ", "\"asdf\" fdsh \\\t\r".to_string()));
-        test_parser!(filename(r#""a s\"\nd\0f" "#) -> @(" ", "a s\"\nd\0f".to_string()));
+        test_parser!(filename(r#""a/My Project/src/foo.rs" "#, EscapeSet::Git) -> @(" ", "a/My Project/src/foo.rs".to_string()));
+        test_parser!(filename(r#""\"asdf\" fdsh \\\t\r" "#, EscapeSet::Git) -> @(" ", "\"asdf\" fdsh \\\t\r".to_string()));
+        test_parser!(filename(r#""a s\"\nd\0f" "#, EscapeSet::Git) -> @(" ", "a s\"\nd\0f".to_string()));
         Ok(())
     }
 
     #[test]
     fn test_header_line_contents() -> ParseResult<'static, ()> {
-        test_parser!(header_line_content("lao\n") -> @("\n", File {
+        test_parser!(header_line_content("lao\n", EscapeSet::Git) -> @("\n", File {
             path: "lao".into(),
+            escaped: false,
             meta: None,
         }));
 
-        test_parser!(header_line_content("lao\t2002-02-21 23:30:39.942229878 -0800\n") -> @(
+        test_parser!(header_line_content("lao\t2002-02-21 23:30:39.942229878 -0800\n", EscapeSet::Git) -> @(
             "\n",
             File {
                 path: "lao".into(),
+                escaped: false,
                 meta: Some(FileMetadata::DateTime(
                     DateTime::parse_from_rfc3339("2002-02-21T23:30:39.942229878-08:00").unwrap()
                 )),
             },
         ));
 
-        test_parser!(header_line_content("lao\t2002-02-21 23:30:39 -0800\n") -> @(
+        test_parser!(header_line_content("lao\t2002-02-21 23:30:39 -0800\n", EscapeSet::Git) -> @(
             "\n",
             File {
                 path: "lao".into(),
+                escaped: false,
                 meta: Some(FileMetadata::DateTime(
                     DateTime::parse_from_rfc3339("2002-02-21T23:30:39-08:00").unwrap()
                 )),
             },
         ));
 
-        test_parser!(header_line_content("lao\t08f78e0addd5bf7b7aa8887e406493e75e8d2b55\n") -> @(
+        test_parser!(header_line_content("lao\t08f78e0addd5bf7b7aa8887e406493e75e8d2b55\n", EscapeSet::Git) -> @(
             "\n",
             File {
                 path: "lao".into(),
+                escaped: false,
                 meta: Some(FileMetadata::Other("08f78e0addd5bf7b7aa8887e406493e75e8d2b55".into()))
             },
         ));
@@ -389,15 +2573,17 @@ mod tests {
         let sample = "\
 --- lao	2002-02-21 23:30:39.942229878 -0800
 +++ tzu	2002-02-21 23:30:50.442260588 -0800\n";
-        test_parser!(headers(sample) -> (
+        test_parser!(headers(sample, EscapeSet::Git) -> (
             File {
                 path: "lao".into(),
+                escaped: false,
                 meta: Some(FileMetadata::DateTime(
                     DateTime::parse_from_rfc3339("2002-02-21T23:30:39.942229878-08:00").unwrap()
                 )),
             },
             File {
                 path: "tzu".into(),
+                escaped: false,
                 meta: Some(FileMetadata::DateTime(
                     DateTime::parse_from_rfc3339("2002-02-21T23:30:50.442260588-08:00").unwrap()
                 )),
@@ -407,49 +2593,143 @@ mod tests {
         let sample2 = "\
 --- lao
 +++ tzu\n";
-        test_parser!(headers(sample2) -> (
-            File {path: "lao".into(), meta: None},
-            File {path: "tzu".into(), meta: None},
+        test_parser!(headers(sample2, EscapeSet::Git) -> (
+            File {path: "lao".into(), escaped: false, meta: None},
+            File {path: "tzu".into(), escaped: false, meta: None},
         ));
 
         let sample2b = "\
 --- lao	
 +++ tzu	\n";
-        test_parser!(headers(sample2b) -> (
-            File {path: "lao".into(), meta: None},
-            File {path: "tzu".into(), meta: None},
+        test_parser!(headers(sample2b, EscapeSet::Git) -> (
+            File {path: "lao".into(), escaped: false, meta: None},
+            File {path: "tzu".into(), escaped: false, meta: None},
         ));
 
         let sample3 = "\
 --- lao	08f78e0addd5bf7b7aa8887e406493e75e8d2b55
 +++ tzu	e044048282ce75186ecc7a214fd3d9ba478a2816\n";
-        test_parser!(headers(sample3) -> (
+        test_parser!(headers(sample3, EscapeSet::Git) -> (
             File {
                 path: "lao".into(),
+                escaped: false,
                 meta: Some(FileMetadata::Other("08f78e0addd5bf7b7aa8887e406493e75e8d2b55".into())),
             },
             File {
                 path: "tzu".into(),
+                escaped: false,
                 meta: Some(FileMetadata::Other("e044048282ce75186ecc7a214fd3d9ba478a2816".into())),
             },
         ));
         Ok(())
     }
 
+    #[test]
+    fn test_git_diff_paths() -> ParseResult<'static, ()> {
+        test_parser!(git_diff_paths("a/foo b/foo") -> ("a/foo".into(), "b/foo".into()));
+        test_parser!(git_diff_paths("a/my file b/my file") -> (
+            "a/my file".into(),
+            "b/my file".into(),
+        ));
+        test_parser!(git_diff_paths("a/old name b/new name") -> (
+            "a/old name".into(),
+            "b/new name".into(),
+        ));
+        test_parser!(git_diff_paths(r#""a/tab\tname" "b/tab\tname""#) -> (
+            "a/tab\tname".into(),
+            "b/tab\tname".into(),
+        ));
+        test_parser!(git_diff_paths("a/foo b/foo\n") -> @("\n", ("a/foo".into(), "b/foo".into())));
+        Ok(())
+    }
+
+    #[test]
+    fn test_git_header() -> ParseResult<'static, ()> {
+        let sample = "\
+diff --git a/script.sh b/script.sh
+old mode 100644
+new mode 100755
+index 1f38447..8946660
+";
+        test_parser!(git_header(sample) -> GitMetadata {
+            old_path: "a/script.sh".into(),
+            new_path: "b/script.sh".into(),
+            escaped_paths: false,
+            old_hash: Some("1f38447"),
+            new_hash: Some("8946660"),
+            old_mode: Some(0o100644),
+            new_mode: Some(0o100755),
+            kind: FileChangeKind::Modified,
+            similarity: None,
+            dissimilarity: None,
+            operation: None,
+        });
+
+        let sample2 = "\
+diff --git a/modified_file b/modified_file
+index c7921f5..8946660 100644
+--- a/modified_file
+";
+        test_parser!(git_header(sample2) -> @("--- a/modified_file\n", GitMetadata {
+            old_path: "a/modified_file".into(),
+            new_path: "b/modified_file".into(),
+            escaped_paths: false,
+            old_hash: Some("c7921f5"),
+            new_hash: Some("8946660"),
+            old_mode: Some(0o100644),
+            new_mode: Some(0o100644),
+            kind: FileChangeKind::Modified,
+            similarity: None,
+            dissimilarity: None,
+            operation: None,
+        }));
+        Ok(())
+    }
+
+    #[test]
+    fn test_git_header_copy() -> ParseResult<'static, ()> {
+        let sample = "\
+diff --git a/config.toml b/config.example.toml
+similarity index 90%
+copy from config.toml
+copy to \"config example.toml\"
+";
+        test_parser!(git_header(sample) -> GitMetadata {
+            old_path: "a/config.toml".into(),
+            new_path: "b/config.example.toml".into(),
+            escaped_paths: false,
+            old_hash: None,
+            new_hash: None,
+            old_mode: None,
+            new_mode: None,
+            kind: FileChangeKind::Modified,
+            similarity: Some(90),
+            dissimilarity: None,
+            operation: Some(FileOperation::Copied {
+                from: "config.toml".into(),
+                to: "config example.toml".into(),
+                escaped: false,
+            }),
+        });
+        Ok(())
+    }
+
     #[test]
     fn test_headers_crlf() -> ParseResult<'static, ()> {
         let sample = "\
 --- lao	2002-02-21 23:30:39.942229878 -0800\r
 +++ tzu	2002-02-21 23:30:50.442260588 -0800\r\n";
-        test_parser!(headers(sample) -> (
+        test_parser!(headers(sample, EscapeSet::Git) -> (
             File {
                 path: "lao".into(),
+                escaped: false,
                 meta: Some(FileMetadata::DateTime(
                     DateTime::parse_from_rfc3339("2002-02-21T23:30:39.942229878-08:00").unwrap()
                 )),
             },
             File {
                 path: "tzu".into(),
+                escaped: false,
                 meta: Some(FileMetadata::DateTime(
                     DateTime::parse_from_rfc3339("2002-02-21T23:30:50.442260588-08:00").unwrap()
                 )),
@@ -509,6 +2789,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_chunk_counts() -> ParseResult<'static, ()> {
+        // Lines past the counts are left for the next parser, even if they look like hunk lines
+        let sample = "@@ -1,2 +1,1 @@\n-a\n\n+b\n";
+        let expected = Hunk {
+            old_range: Range { start: 1, count: 2 },
+            new_range: Range { start: 1, count: 1 },
+            range_hint: "",
+            lines: vec![Line::Remove("a"), Line::Context("")],
+        };
+        test_parser!(chunk(sample) -> @("+b\n", expected));
+
+        assert!(chunk(Input::new("@@ -1,2 +1,2 @@\n a\n")).is_err());
+        assert!(chunk(Input::new("@@ -1,1 +1,1 @@\n-a\n-b\n+c\n")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_newline_indicator() -> ParseResult<'static, ()> {
+        test_parser!(no_newline_indicator("\\ No newline at end of file\n") -> true);
+        test_parser!(no_newline_indicator("\\ No newline at end of file") -> true);
+        test_parser!(no_newline_indicator(
+            "\\ Pas de fin de ligne à la fin du fichier\r\n") -> true);
+        test_parser!(no_newline_indicator("\\ Kein Zeilenumbruch am Dateiende.\n+x\n") ->
+            @("+x\n", true));
+        test_parser!(no_newline_indicator(" context\n") -> @(" context\n", false));
+        test_parser!(english_no_newline_indicator("\\ No newline at end of file\n") -> true);
+        test_parser!(english_no_newline_indicator("\\ Kein Zeilenumbruch am Dateiende.\n") ->
+            @("\\ Kein Zeilenumbruch am Dateiende.\n", false));
+        Ok(())
+    }
+
     #[test]
     fn test_patch() -> ParseResult<'static, ()> {
         // https://www.gnu.org/software/diffutils/manual/html_node/Example-Unified.html
@@ -536,16 +2848,22 @@ mod tests {
         let expected = Patch {
             old: File {
                 path: "lao".into(),
+                escaped: false,
                 meta: Some(FileMetadata::DateTime(
                     DateTime::parse_from_rfc3339("2002-02-21T23:30:39.942229878-08:00").unwrap(),
                 )),
             },
             new: File {
                 path: "tzu".into(),
+                escaped: false,
                 meta: Some(FileMetadata::DateTime(
                     DateTime::parse_from_rfc3339("2002-02-21T23:30:50.442260588-08:00").unwrap(),
                 )),
             },
+            git: None,
+            svn: None,
+            cvs: None,
+            perforce: None,
             hunks: vec![
                 Hunk {
                     old_range: Range { start: 1, count: 7 },
@@ -577,7 +2895,12 @@ mod tests {
                     ],
                 },
             ],
-            end_newline: true,
+            binary: None,
+            old_end_newline: true,
+            new_end_newline: true,
+            line_ending: LineEnding::Lf,
+            byte_order_mark: false,
+            preamble: None,
         };
 
         test_parser!(patch(sample) -> expected);
@@ -0,0 +1,133 @@
+use std::fmt;
+
+use crate::ast::Line;
+use crate::parser::{parse_range_diff, ParseError};
+
+/// The output of `git range-diff`, which compares two versions of a series of commits
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RangeDiff<'a> {
+    /// Each commit of either series, paired with its counterpart in the other series if any
+    pub entries: Vec<RangeDiffEntry<'a>>,
+}
+
+impl<'a> RangeDiff<'a> {
+    /// Attempt to parse the output of `git range-diff --no-color`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{Line, RangeDiff, RangeDiffStatus};
+    /// let sample = "\
+    /// 1:  6d2af67 ! 1:  56d16ca Change two
+    ///     @@ f.txt
+    ///      @@
+    ///       one
+    ///      -two
+    ///     -+2
+    ///     ++TWO
+    ///       three
+    /// 2:  033c913 = 2:  2873b86 Add g
+    /// 3:  fc81707 < -:  ------- Add h
+    /// -:  ------- > 3:  67e48e7 Add i\n";
+    ///
+    /// let range_diff = RangeDiff::parse(sample)?;
+    /// let entry = &range_diff.entries[0];
+    /// assert_eq!(entry.status, RangeDiffStatus::Changed);
+    /// assert_eq!(entry.subject, "Change two");
+    /// assert_eq!(entry.sections[0].lines[3], Line::Remove("+2"));
+    /// assert_eq!(range_diff.entries[3].old, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse(s: &'a str) -> Result<Self, ParseError<'a>> {
+        parse_range_diff(s)
+    }
+}
+
+impl<'a> fmt::Display for RangeDiff<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Like git, align the commit numbers and fill in missing commits with dashes as long as
+        // the abbreviated hashes
+        let commits = || self.entries.iter().flat_map(|e| e.old.iter().chain(&e.new));
+        let max_index = commits().map(|commit| commit.index).max().unwrap_or(0);
+        let width = (max_index + 1).to_string().len();
+        let dashes = "-".repeat(commits().next().map_or(7, |commit| commit.hash.len()));
+        let commit = |f: &mut fmt::Formatter, commit: &Option<RangeDiffCommit>| match commit {
+            Some(commit) => write!(f, "{:>width$}:  {}", commit.index, commit.hash),
+            None => write!(f, "{:>width$}:  {}", "-", dashes),
+        };
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            commit(f, &entry.old)?;
+            let status = match entry.status {
+                RangeDiffStatus::Unchanged => '=',
+                RangeDiffStatus::Changed => '!',
+                RangeDiffStatus::Removed => '<',
+                RangeDiffStatus::Added => '>',
+            };
+            write!(f, " {} ", status)?;
+            commit(f, &entry.new)?;
+            write!(f, " {}", entry.subject)?;
+            for section in &entry.sections {
+                write!(f, "\n    @@{}", section.header)?;
+                for line in &section.lines {
+                    write!(f, "\n    {}", line)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A commit of either series and its counterpart, from one line of `git range-diff`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RangeDiffEntry<'a> {
+    /// The commit in the old series, or `None` if the commit was added
+    pub old: Option<RangeDiffCommit<'a>>,
+    /// The commit in the new series, or `None` if the commit was removed
+    pub new: Option<RangeDiffCommit<'a>>,
+    /// How the commit changed between the series
+    pub status: RangeDiffStatus,
+    /// The subject line of the commit
+    pub subject: &'a str,
+    /// The diff between the two versions of the commit, for changed commits
+    pub sections: Vec<RangeDiffSection<'a>>,
+}
+
+/// A commit in one of the series compared by a [`RangeDiff`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RangeDiffCommit<'a> {
+    /// The position of the commit in its series, starting at 1
+    pub index: u32,
+    /// The abbreviated commit hash
+    pub hash: &'a str,
+}
+
+/// How a commit changed between the series compared by a [`RangeDiff`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RangeDiffStatus {
+    /// The commits have the same patch, marked with `=`
+    Unchanged,
+    /// The commits have different patches, marked with `!`
+    Changed,
+    /// The commit is only in the old series, marked with `<`
+    Removed,
+    /// The commit is only in the new series, marked with `>`
+    Added,
+}
+
+/// A part of the diff between two versions of a commit, starting with a line like `@@ f.txt`
+///
+/// The lines are a diff of the two patches, so the text of each line starts with the marker of
+/// the patch line itself, e.g. `Line::Remove("+2")` for an added line that is no longer added.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RangeDiffSection<'a> {
+    /// The text after `@@`, e.g. ` f.txt` or ` Commit message`
+    pub header: &'a str,
+    /// The lines of the section
+    pub lines: Vec<Line<'a>>,
+}
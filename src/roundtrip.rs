@@ -0,0 +1,218 @@
+use std::fmt;
+
+use crate::ast::*;
+use crate::options::ParserOptions;
+use crate::parser::{parse_multiple_patches, ParseError};
+
+/// A report describing how a patch file changed after being parsed, formatted, and parsed again
+///
+/// Produced by [`verify_roundtrip`]. Attach the [`Display`](fmt::Display) output of this report
+/// when filing a round-trip bug.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RoundtripReport {
+    /// The patch file produced by formatting the parsed input
+    pub output: String,
+    /// The byte offset of the first difference between the input and `output`, if any
+    pub first_differing_byte: Option<usize>,
+    /// The first field that differs between the two parses, e.g. `patches[0].hunks[1].range_hint`
+    pub differing_field: Option<String>,
+    /// The error produced if `output` could not be parsed again
+    pub reparse_error: Option<String>,
+}
+
+impl RoundtripReport {
+    /// True if re-parsing the formatted output produced exactly the same patches
+    ///
+    /// The formatted text may still differ from the input (see `first_differing_byte`) in ways
+    /// that aren't represented in the parsed patches, like empty lines after a patch.
+    pub fn is_lossless(&self) -> bool {
+        self.differing_field.is_none() && self.reparse_error.is_none()
+    }
+}
+
+impl fmt::Display for RoundtripReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.first_differing_byte {
+            Some(offset) => write!(f, "Output differs from input starting at byte {}", offset)?,
+            None => write!(f, "Output is identical to input")?,
+        }
+        if let Some(error) = &self.reparse_error {
+            write!(f, "\nFailed to re-parse output: {}", error)?;
+        }
+        if let Some(field) = &self.differing_field {
+            write!(f, "\nRe-parsed output differs at {}", field)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a patch file, format it, parse the result again, and report any divergence.
+///
+/// An error is only returned if the original input fails to parse.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), patch::ParseError<'static>> {
+/// let sample = "\
+/// --- before.py
+/// +++ after.py
+/// @@ -1,1 +1,1 @@
+/// -bacon
+/// +python\n";
+///
+/// let report = patch::verify_roundtrip(sample)?;
+/// assert!(report.is_lossless());
+/// assert_eq!(report.first_differing_byte, None);
+/// # Ok(())
+/// # }
+/// ```
+pub fn verify_roundtrip(input: &str) -> Result<RoundtripReport, ParseError<'_>> {
+    let patches = parse_multiple_patches(input, &ParserOptions::default())?;
+    let output: String = patches
+        .iter()
+        .map(|patch| format!("{}{}", patch, patch.line_ending.as_str()))
+        .collect();
+
+    let first_differing_byte = input
+        .bytes()
+        .zip(output.bytes())
+        .position(|(a, b)| a != b)
+        .or_else(|| {
+            if input.len() == output.len() {
+                None
+            } else {
+                Some(input.len().min(output.len()))
+            }
+        });
+
+    let (differing_field, reparse_error) =
+        match parse_multiple_patches(&output, &ParserOptions::default()) {
+            Ok(reparsed) => (first_differing_field(&patches, &reparsed), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+
+    Ok(RoundtripReport {
+        output,
+        first_differing_byte,
+        differing_field,
+        reparse_error,
+    })
+}
+
+fn first_differing_field(before: &[Patch], after: &[Patch]) -> Option<String> {
+    if before.len() != after.len() {
+        return Some("patches.len()".to_string());
+    }
+    before
+        .iter()
+        .zip(after)
+        .enumerate()
+        .find_map(|(i, (a, b))| patch_field(a, b).map(|field| format!("patches[{}]{}", i, field)))
+}
+
+fn patch_field(a: &Patch, b: &Patch) -> Option<String> {
+    if a.old != b.old {
+        return Some(".old".to_string());
+    }
+    if a.new != b.new {
+        return Some(".new".to_string());
+    }
+    if a.git != b.git {
+        return Some(".git".to_string());
+    }
+    if a.svn != b.svn {
+        return Some(".svn".to_string());
+    }
+    if a.cvs != b.cvs {
+        return Some(".cvs".to_string());
+    }
+    if a.perforce != b.perforce {
+        return Some(".perforce".to_string());
+    }
+    if a.hunks.len() != b.hunks.len() {
+        return Some(".hunks.len()".to_string());
+    }
+    for (i, (x, y)) in a.hunks.iter().zip(&b.hunks).enumerate() {
+        if let Some(field) = hunk_field(x, y) {
+            return Some(format!(".hunks[{}]{}", i, field));
+        }
+    }
+    if a.binary != b.binary {
+        return Some(".binary".to_string());
+    }
+    if a.old_end_newline != b.old_end_newline {
+        return Some(".old_end_newline".to_string());
+    }
+    if a.new_end_newline != b.new_end_newline {
+        return Some(".new_end_newline".to_string());
+    }
+    if a.line_ending != b.line_ending {
+        return Some(".line_ending".to_string());
+    }
+    if a.byte_order_mark != b.byte_order_mark {
+        return Some(".byte_order_mark".to_string());
+    }
+    if a.preamble != b.preamble {
+        return Some(".preamble".to_string());
+    }
+    // Catch-all in case a field was missed above
+    if a != b {
+        return Some(String::new());
+    }
+    None
+}
+
+fn hunk_field(a: &Hunk, b: &Hunk) -> Option<String> {
+    if a.old_range != b.old_range {
+        return Some(".old_range".to_string());
+    }
+    if a.new_range != b.new_range {
+        return Some(".new_range".to_string());
+    }
+    if a.range_hint != b.range_hint {
+        return Some(".range_hint".to_string());
+    }
+    if a.lines.len() != b.lines.len() {
+        return Some(".lines.len()".to_string());
+    }
+    a.lines
+        .iter()
+        .zip(&b.lines)
+        .position(|(x, y)| x != y)
+        .map(|i| format!(".lines[{}]", i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_crlf_output_is_identical() -> Result<(), ParseError<'static>> {
+        let sample = "--- old.txt\r\n+++ new.txt\r\n@@ -1,1 +1,1 @@\r\n x\r\n";
+        let report = verify_roundtrip(sample)?;
+        assert_eq!(report.first_differing_byte, None);
+        assert!(report.is_lossless());
+
+        let sample = "--- old.txt\n+++ new.txt\n@@ -1,1 +1,1 @@\n x\n\n";
+        let report = verify_roundtrip(sample)?;
+        assert_eq!(report.first_differing_byte, Some(43));
+        assert!(report.is_lossless());
+        Ok(())
+    }
+
+    #[test]
+    fn test_differing_field() -> Result<(), ParseError<'static>> {
+        let sample = "--- old.txt\n+++ new.txt\n@@ -1,1 +1,1 @@\n x\n";
+        let patches = parse_multiple_patches(sample, &ParserOptions::default())?;
+        let mut changed = patches.clone();
+        changed[0].hunks[0].lines[0] = Line::Add("x");
+        assert_eq!(first_differing_field(&patches, &patches), None);
+        assert_eq!(
+            first_differing_field(&patches, &changed),
+            Some("patches[0].hunks[0].lines[0]".to_string())
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use crate::ast::{File, GitMetadata, Range};
+
+/// The headers of a patch without its hunk lines, from [`Patch::scan_headers`]
+///
+/// [`Patch::scan_headers`]: crate::Patch::scan_headers
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PatchHeaders<'a> {
+    /// The file information of the `-` side of the diff, line prefix: `---`
+    pub old: File<'a>,
+    /// The file information of the `+` side of the diff, line prefix: `+++`
+    pub new: File<'a>,
+    /// The extended header information provided by `git diff`, if any
+    pub git: Option<GitMetadata<'a>>,
+    /// The `@@` header of each hunk
+    pub hunks: Vec<HunkHeader<'a>>,
+}
+
+/// The `@@` header line of a hunk
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HunkHeader<'a> {
+    /// The range of lines in the old file that this hunk represents
+    pub old_range: Range,
+    /// The range of lines in the new file that this hunk represents
+    pub new_range: Range,
+    /// Any trailing text after the hunk's range information
+    pub range_hint: &'a str,
+}
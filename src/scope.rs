@@ -0,0 +1,159 @@
+/// The function, type, or other definition that a hunk modifies, as named by its range hint
+///
+/// Produced by [`Hunk::scope`](crate::Hunk::scope).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Scope<'a> {
+    /// What kind of definition the hint names
+    pub kind: ScopeKind,
+    /// The name of the definition, e.g. `main` for `fn main() {`
+    pub name: &'a str,
+}
+
+/// The kind of definition named by a [`Scope`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ScopeKind {
+    /// A function or method, e.g. `fn`, `def`, `func`, `function`, or a C-style `int main(void)`
+    Function,
+    /// A type definition, e.g. `class`, `struct`, `enum`, `trait` or `interface`
+    Type,
+    /// A Rust `impl` block, named after the type it implements
+    Impl,
+    /// A module or namespace, e.g. `mod`, `module`, `namespace` or `package`
+    Module,
+}
+
+const CONTROL_KEYWORDS: &[&str] = &["if", "for", "while", "switch", "return", "catch"];
+
+// Interprets the text git writes after a hunk's ranges, which is usually the line that starts the
+// enclosing definition. This is a heuristic covering common languages, not a parser for any.
+pub(crate) fn scope_of(hint: &str) -> Option<Scope<'_>> {
+    for word in hint.split_whitespace() {
+        // The keyword may be followed by generics, e.g. `impl<'a>`
+        let end = word.find(|ch: char| !ch.is_alphanumeric());
+        let keyword = &word[..end.unwrap_or(word.len())];
+        let kind = match keyword {
+            "fn" | "def" | "func" | "function" | "sub" => ScopeKind::Function,
+            "class" | "struct" | "enum" | "union" | "trait" | "interface" => ScopeKind::Type,
+            "impl" => ScopeKind::Impl,
+            "mod" | "module" | "namespace" | "package" => ScopeKind::Module,
+            _ => continue,
+        };
+        // Everything after the keyword, which is a substring of `hint`
+        let start = word.as_ptr() as usize - hint.as_ptr() as usize + keyword.len();
+        let rest = hint[start..].trim_start();
+        let rest = match kind {
+            // Go methods have a receiver before their name, e.g. `func (r *Repo) Name()`
+            ScopeKind::Function => skip_delimited(rest, '(', ')'),
+            // Name impls after their type, e.g. `impl<'a> Display for Patch<'a> {`
+            ScopeKind::Impl => {
+                let rest = skip_delimited(rest, '<', '>');
+                rest.find(" for ")
+                    .map_or(rest, |i| rest[i + 5..].trim_start())
+            }
+            _ => rest,
+        };
+        let name = identifier(rest);
+        return if name.is_empty() {
+            None
+        } else {
+            Some(Scope { kind, name })
+        };
+    }
+
+    // Otherwise look for a C-style function definition like `static int main(void)`
+    let before_paren = hint[..hint.find('(')?].trim_end();
+    let start = before_paren
+        .char_indices()
+        .rev()
+        .find(|&(_, ch)| !is_identifier_char(ch))
+        .map_or(0, |(i, ch)| i + ch.len_utf8());
+    let name = &before_paren[start..];
+    if name.is_empty() || CONTROL_KEYWORDS.contains(&name) {
+        return None;
+    }
+    Some(Scope {
+        kind: ScopeKind::Function,
+        name,
+    })
+}
+
+// Skips a leading `open`..`close` group, e.g. generic parameters, and any whitespace after it
+fn skip_delimited(s: &str, open: char, close: char) -> &str {
+    if !s.starts_with(open) {
+        return s;
+    }
+    let mut depth = 0;
+    for (i, ch) in s.char_indices() {
+        if ch == open {
+            depth += 1;
+        } else if ch == close {
+            depth -= 1;
+            if depth == 0 {
+                return s[i + 1..].trim_start();
+            }
+        }
+    }
+    s
+}
+
+fn identifier(s: &str) -> &str {
+    let end = s.find(|ch| !is_identifier_char(ch)).unwrap_or(s.len());
+    &s[..end]
+}
+
+fn is_identifier_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '_' | '$' | '.' | ':')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_scope_of() {
+        use ScopeKind::*;
+        for (hint, expected) in [
+            ("", None),
+            (" fn main() {", Some((Function, "main"))),
+            (
+                " pub(crate) async fn parse<'a>(s: &'a str)",
+                Some((Function, "parse")),
+            ),
+            (
+                " impl<'a> GameGenerator<'a> {",
+                Some((Impl, "GameGenerator")),
+            ),
+            (
+                " impl<'a> fmt::Display for Patch<'a> {",
+                Some((Impl, "Patch")),
+            ),
+            (" pub struct Patch<'a> {", Some((Type, "Patch"))),
+            (" class Greeter(object):", Some((Type, "Greeter"))),
+            ("     def greet(self, name):", Some((Function, "greet"))),
+            (
+                " func (r *Repo) Commit(msg string) error {",
+                Some((Function, "Commit")),
+            ),
+            (
+                " export default function render() {",
+                Some((Function, "render")),
+            ),
+            (" namespace detail {", Some((Module, "detail"))),
+            (
+                " static int main(int argc, char **argv)",
+                Some((Function, "main")),
+            ),
+            (" int Foo::bar() const", Some((Function, "Foo::bar"))),
+            (" «foo(x)", Some((Function, "foo"))),
+            (" x→bar(y)", Some((Function, "bar"))),
+            (" ¿main()", Some((Function, "main"))),
+            (" void café(void)", Some((Function, "café"))),
+            (" if (x) {", None),
+            (" Some heading", None),
+        ] {
+            let expected = expected.map(|(kind, name)| Scope { kind, name });
+            assert_eq!(scope_of(hint), expected, "{:?}", hint);
+        }
+    }
+}
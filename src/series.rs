@@ -0,0 +1,153 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::ast::Patch;
+use crate::parser::{parse_series, ParseError};
+
+/// A quilt `series` file, which lists the patches of a stack in the order they apply
+///
+/// Patches are applied with `-p1` unless their line says otherwise, and comments after `#` are
+/// ignored.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), patch::ParseError<'static>> {
+/// # use patch::Series;
+/// let sample = "\
+/// ## Upstream fixes
+/// fix-build.patch
+/// debian/paths.diff -p0
+/// revert-feature.patch -p1 -R\n";
+///
+/// let series = Series::parse(sample)?;
+/// assert_eq!(series.entries.len(), 3);
+/// assert_eq!(series.entries[1].name, "debian/paths.diff");
+/// assert_eq!(series.entries[1].strip, 0);
+/// assert!(series.entries[2].reverse);
+/// assert_eq!(series.to_string(), "fix-build.patch\ndebian/paths.diff -p0\nrevert-feature.patch -R");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Series<'a> {
+    /// The patches, in the order they apply
+    pub entries: Vec<SeriesEntry<'a>>,
+}
+
+impl<'a> Series<'a> {
+    /// Attempt to parse a quilt `series` file
+    pub fn parse(s: &'a str) -> Result<Self, ParseError<'a>> {
+        parse_series(s)
+    }
+
+    /// Read the patch file of each entry from `dir`, usually the `patches` directory next to the
+    /// series file, for [`Series::parse_patches`]
+    pub fn read_patch_files(&self, dir: impl AsRef<Path>) -> io::Result<Vec<String>> {
+        let dir = dir.as_ref();
+        self.entries
+            .iter()
+            .map(|entry| fs::read_to_string(dir.join(entry.name)))
+            .collect()
+    }
+
+    /// Parse the patch files of the entries, in the same order, like the ones from
+    /// [`Series::read_patch_files`]. Each of them must contain at least one patch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there isn't one file for each entry.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::Series;
+    /// let series = Series::parse("fix-typo.patch\n")?;
+    /// let files = vec!["\
+    /// Description: Fix a typo in the greeting
+    /// --- a/hello.txt
+    /// +++ b/hello.txt
+    /// @@ -1 +1 @@
+    /// -helo
+    /// +hello\n"
+    ///     .to_owned()];
+    ///
+    /// let stack = series.parse_patches(&files).unwrap();
+    /// assert_eq!(stack.patches[0].entry.name, "fix-typo.patch");
+    /// assert_eq!(stack.patches[0].files[0].new.path, "b/hello.txt");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_patches(&self, files: &'a [String]) -> Result<PatchSeries<'a>, ParseError<'a>> {
+        assert_eq!(
+            self.entries.len(),
+            files.len(),
+            "need one patch file for each entry"
+        );
+        let mut patches = Vec::new();
+        for (entry, file) in self.entries.iter().zip(files) {
+            patches.push(SeriesPatch {
+                entry: entry.clone(),
+                files: Patch::from_multiple(file)?,
+            });
+        }
+        Ok(PatchSeries { patches })
+    }
+}
+
+impl<'a> fmt::Display for Series<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// A line of a quilt [`Series`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SeriesEntry<'a> {
+    /// The path of the patch file, relative to the patches directory
+    pub name: &'a str,
+    /// The number of leading path components to strip from the patch's file names, from `-pN`.
+    /// quilt uses 1 when there is no option.
+    pub strip: u32,
+    /// Whether the patch is applied in reverse, from `-R`
+    pub reverse: bool,
+}
+
+impl<'a> fmt::Display for SeriesEntry<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if self.strip != 1 {
+            write!(f, " -p{}", self.strip)?;
+        }
+        if self.reverse {
+            write!(f, " -R")?;
+        }
+        Ok(())
+    }
+}
+
+/// The parsed patch files of a [`Series`], from [`Series::parse_patches`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PatchSeries<'a> {
+    /// The patch files, in the order they apply
+    pub patches: Vec<SeriesPatch<'a>>,
+}
+
+/// A patch file of a [`PatchSeries`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SeriesPatch<'a> {
+    /// The line of the series file that names the patch file
+    pub entry: SeriesEntry<'a>,
+    /// The patches for each file in the patch file. Any description before them is in the first
+    /// one's [`Patch::preamble`].
+    pub files: Vec<Patch<'a>>,
+}
@@ -0,0 +1,62 @@
+use std::borrow::Cow;
+
+use crate::parser::{parse_numstat, parse_stat, ParseError};
+
+/// The number of lines a patch adds and removes from a file, like one line of
+/// `git diff --numstat` or `git diff --stat`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FileStat<'a> {
+    /// The path of the file. For renames, git writes both paths, e.g. `old.txt => new.txt`.
+    pub path: Cow<'a, str>,
+    /// The number of added lines
+    pub additions: u64,
+    /// The number of removed lines
+    pub deletions: u64,
+    /// True for binary files, which have no line counts
+    pub binary: bool,
+}
+
+impl<'a> FileStat<'a> {
+    /// Attempt to parse the output of `git diff --numstat`, with one line per file
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::FileStat;
+    /// let stats = FileStat::parse_numstat("200\t0\tbig.txt\n-\t-\tbin.dat\n")?;
+    /// assert_eq!(stats[0].additions, 200);
+    /// assert!(stats[1].binary);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_numstat(s: &'a str) -> Result<Vec<Self>, ParseError<'a>> {
+        parse_numstat(s)
+    }
+
+    /// Attempt to parse the output of `git diff --stat`, with one line per file and an optional
+    /// summary line at the end
+    ///
+    /// git scales down the `+` and `-` graph of large changes to fit the terminal, so the
+    /// additions and deletions of those files are estimated from the graph. Their sum is exact.
+    /// Long paths that git shortened with `...` are kept as they are.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::FileStat;
+    /// let sample = "\
+    /// \x20hello.txt | 2 +-
+    /// \x201 file changed, 1 insertion(+), 1 deletion(-)\n";
+    ///
+    /// let stats = FileStat::parse_stat(sample)?;
+    /// assert_eq!(stats[0].path, "hello.txt");
+    /// assert_eq!((stats[0].additions, stats[0].deletions), (1, 1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_stat(s: &'a str) -> Result<Vec<Self>, ParseError<'a>> {
+        parse_stat(s)
+    }
+}
@@ -0,0 +1,34 @@
+use crate::ast::Span;
+
+/// Something the parser worked around instead of failing, from
+/// [`Patch::from_multiple_with_warnings`]
+///
+/// [`Patch::from_multiple_with_warnings`]: crate::Patch::from_multiple_with_warnings
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Warning {
+    /// What the parser worked around
+    pub kind: WarningKind,
+    /// Where in the input it is
+    pub span: Span,
+}
+
+/// The kind of a [`Warning`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum WarningKind {
+    /// Text before a patch, like a commit message, which is kept in
+    /// [`Patch::preamble`](crate::Patch::preamble)
+    Preamble,
+    /// Lines right after the last hunk of a patch that look like more of its lines, e.g. `+c`,
+    /// but are past its ranges. They are read as text before the next patch. This usually means
+    /// the ranges of the hunk are miscounted.
+    ExtraHunkLines,
+    /// An empty line in a hunk was read as an empty context line, whose leading space was
+    /// probably stripped as trailing whitespace
+    EmptyContextLine,
+    /// A hunk has more or fewer lines than its ranges say. This is only allowed when
+    /// [`ParserOptions::strict_counts`](crate::ParserOptions::strict_counts) is off.
+    HunkLineCount,
+    /// A hunk header has text right after its closing `@@`, without a space before it. It is
+    /// kept in [`Hunk::range_hint`](crate::Hunk::range_hint).
+    TextAfterHunkHeader,
+}
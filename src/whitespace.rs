@@ -0,0 +1,112 @@
+use std::borrow::Cow;
+
+use crate::ast::{Hunk, Line};
+
+/// Make tabs and trailing spaces in a line visible, e.g. for rendering the text of a [`Line`]
+/// in a review comment. Tabs are replaced by `tab` and spaces at the end of the line by
+/// `trailing_space`; git's own choices would be `'→'` and `'·'`.
+///
+/// Only allocates if there is whitespace to replace.
+///
+/// # Example
+///
+/// ```
+/// let visible = patch::visualize_whitespace("\tlet x = 1;  ", '→', '·');
+/// assert_eq!(visible, "→let x = 1;··");
+/// ```
+pub fn visualize_whitespace(line: &str, tab: char, trailing_space: char) -> Cow<'_, str> {
+    let content = line.trim_end_matches(' ');
+    if !content.contains('\t') && content.len() == line.len() {
+        return Cow::Borrowed(line);
+    }
+
+    let mut output: String = content
+        .chars()
+        .map(|ch| if ch == '\t' { tab } else { ch })
+        .collect();
+    output.extend(std::iter::repeat_n(
+        trailing_space,
+        line.len() - content.len(),
+    ));
+    Cow::Owned(output)
+}
+
+/// A whitespace problem in an added line, from [`Hunk::whitespace_errors`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct WhitespaceError {
+    /// The index of the line in [`Hunk::lines`]
+    pub line: usize,
+    /// What is wrong with the line
+    pub kind: WhitespaceErrorKind,
+}
+
+/// The kind of a [`WhitespaceError`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum WhitespaceErrorKind {
+    /// The line ends in spaces or tabs
+    TrailingWhitespace,
+    /// The line's indentation mixes tabs and spaces
+    MixedIndentation,
+}
+
+// Checks added lines like `git diff --check` does
+pub(crate) fn whitespace_errors(hunk: &Hunk<'_>) -> Vec<WhitespaceError> {
+    let mut errors = Vec::new();
+    for (i, line) in hunk.lines.iter().enumerate() {
+        let text = match line {
+            Line::Add(text) => text.strip_suffix('\r').unwrap_or(text),
+            _ => continue,
+        };
+        if text.ends_with([' ', '\t']) {
+            errors.push(WhitespaceError {
+                line: i,
+                kind: WhitespaceErrorKind::TrailingWhitespace,
+            });
+        }
+        let indent = &text[..text.len() - text.trim_start_matches([' ', '\t']).len()];
+        if indent.contains(' ') && indent.contains('\t') {
+            errors.push(WhitespaceError {
+                line: i,
+                kind: WhitespaceErrorKind::MixedIndentation,
+            });
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_visualize_whitespace() {
+        assert!(matches!(
+            visualize_whitespace("plain text", '→', '·'),
+            Cow::Borrowed("plain text")
+        ));
+        assert_eq!(visualize_whitespace("\t\tx\ty ", '>', '.'), ">>x>y.");
+        assert_eq!(visualize_whitespace("   ", '→', '·'), "···");
+    }
+
+    #[test]
+    fn test_whitespace_errors() -> Result<(), crate::ParseError<'static>> {
+        let hunk =
+            Hunk::parse("@@ -1,2 +1,4 @@\n-old \n+new \n \tcontext \n+ \tmixed\n+\t\tclean\n")?;
+        use WhitespaceErrorKind::*;
+        assert_eq!(
+            whitespace_errors(&hunk),
+            [
+                WhitespaceError {
+                    line: 1,
+                    kind: TrailingWhitespace
+                },
+                WhitespaceError {
+                    line: 3,
+                    kind: MixedIndentation
+                },
+            ]
+        );
+        Ok(())
+    }
+}
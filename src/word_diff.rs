@@ -0,0 +1,178 @@
+use std::fmt;
+
+use crate::ast::{File, GitMetadata, Range};
+use crate::parser::{parse_word_diff_patches, ParseError};
+
+/// A patch from `git diff --word-diff`, which marks changed words inline instead of changed lines
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WordDiffPatch<'a> {
+    /// The file information of the `-` side of the diff, line prefix: `---`
+    pub old: File<'a>,
+    /// The file information of the `+` side of the diff, line prefix: `+++`
+    pub new: File<'a>,
+    /// The extended header lines from `git diff`, if any
+    pub git: Option<GitMetadata<'a>>,
+    /// hunks of differences; each hunk shows one area where the files differ
+    pub hunks: Vec<WordDiffHunk<'a>>,
+}
+
+impl<'a> WordDiffPatch<'a> {
+    /// Attempt to parse as many word diff patches as possible from the given string, e.g. the
+    /// output of `git diff --word-diff=plain`. String must contain at least one patch.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), patch::ParseError<'static>> {
+    /// # use patch::{WordDiffPatch, WordDiffSpan};
+    /// let sample = "\
+    /// diff --git a/fox.txt b/fox.txt
+    /// index 4e3f9d2..8b1c0a7 100644
+    /// --- a/fox.txt
+    /// +++ b/fox.txt
+    /// @@ -1,2 +1,2 @@
+    /// the [-quick-]{+slow+} brown fox
+    /// jumps over\n";
+    ///
+    /// let patches = WordDiffPatch::from_multiple(sample)?;
+    /// let line = &patches[0].hunks[0].lines[0];
+    /// assert_eq!(
+    ///     line.spans,
+    ///     [
+    ///         WordDiffSpan::Unchanged("the "),
+    ///         WordDiffSpan::Removed("quick"),
+    ///         WordDiffSpan::Added("slow"),
+    ///         WordDiffSpan::Unchanged(" brown fox"),
+    ///     ]
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_multiple(s: &'a str) -> Result<Vec<Self>, ParseError<'a>> {
+        parse_word_diff_patches(s)
+    }
+}
+
+impl<'a> fmt::Display for WordDiffPatch<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(git) = &self.git {
+            write!(f, "{}", git)?;
+            if self.hunks.is_empty() {
+                return Ok(());
+            }
+            writeln!(f)?;
+        }
+        write!(f, "--- {}", self.old)?;
+        write!(f, "\n+++ {}", self.new)?;
+        for hunk in &self.hunks {
+            write!(f, "\n{}", hunk)?;
+        }
+        Ok(())
+    }
+}
+
+/// One area where the files differ in a [`WordDiffPatch`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WordDiffHunk<'a> {
+    /// The range of lines in the old file that this hunk represents
+    pub old_range: Range,
+    /// The range of lines in the new file that this hunk represents
+    pub new_range: Range,
+    /// Any trailing text after the hunk's range information
+    pub range_hint: &'a str,
+    /// Each line of text in the hunk, with its changed words marked
+    pub lines: Vec<WordDiffLine<'a>>,
+}
+
+impl<'a> fmt::Display for WordDiffHunk<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "@@ -{} +{} @@{}",
+            self.old_range, self.new_range, self.range_hint
+        )?;
+        for line in &self.lines {
+            write!(f, "\n{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// A line of a word diff, split into unchanged, removed and added text
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WordDiffLine<'a> {
+    /// The parts of the line in order. An empty line has no spans.
+    pub spans: Vec<WordDiffSpan<'a>>,
+}
+
+impl<'a> WordDiffLine<'a> {
+    /// Split a line written by `git diff --word-diff=plain` into its spans
+    ///
+    /// Removed text is marked like `[-this-]` and added text like `{+this+}`. A marker that is
+    /// never closed is kept as unchanged text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use patch::{WordDiffLine, WordDiffSpan};
+    /// let line = WordDiffLine::parse("[-c-]{+X Y+}");
+    /// assert_eq!(line.spans, [WordDiffSpan::Removed("c"), WordDiffSpan::Added("X Y")]);
+    /// assert_eq!(line.to_string(), "[-c-]{+X Y+}");
+    /// ```
+    pub fn parse(s: &'a str) -> Self {
+        let mut spans = Vec::new();
+        let mut rest = s;
+        loop {
+            let next = [("[-", "-]"), ("{+", "+}")]
+                .iter()
+                .filter_map(|&(open, close)| Some((rest.find(open)?, open, close)))
+                .min();
+            let (start, open, close) = match next {
+                Some(next) => next,
+                None => break,
+            };
+            let len = match rest[start + 2..].find(close) {
+                Some(len) => len,
+                None => break,
+            };
+            if start > 0 {
+                spans.push(WordDiffSpan::Unchanged(&rest[..start]));
+            }
+            let text = &rest[start + 2..start + 2 + len];
+            spans.push(if open == "[-" {
+                WordDiffSpan::Removed(text)
+            } else {
+                WordDiffSpan::Added(text)
+            });
+            rest = &rest[start + 2 + len + 2..];
+        }
+        if !rest.is_empty() {
+            spans.push(WordDiffSpan::Unchanged(rest));
+        }
+        WordDiffLine { spans }
+    }
+}
+
+impl<'a> fmt::Display for WordDiffLine<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for span in &self.spans {
+            match span {
+                WordDiffSpan::Unchanged(text) => write!(f, "{}", text)?,
+                WordDiffSpan::Removed(text) => write!(f, "[-{}-]", text)?,
+                WordDiffSpan::Added(text) => write!(f, "{{+{}+}}", text)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A part of a [`WordDiffLine`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum WordDiffSpan<'a> {
+    /// Text in both the old and the new line
+    Unchanged(&'a str),
+    /// Text only in the old line, marked like `[-this-]`
+    Removed(&'a str),
+    /// Text only in the new line, marked like `{+this+}`
+    Added(&'a str),
+}
@@ -0,0 +1,61 @@
+//! Pathological inputs that should be handled in linear time instead of backtracking
+
+use patch::Patch;
+
+use pretty_assertions::assert_eq;
+
+const VALID: &str = "\
+--- before.py
++++ after.py
+@@ -1,1 +1,1 @@
+-bacon
++python
+";
+
+#[test]
+fn many_separator_lines_in_preamble() {
+    let sample = "---\n".repeat(100_000) + VALID;
+    let patches = Patch::from_multiple(&sample).unwrap();
+    assert_eq!(patches.len(), 1);
+}
+
+#[test]
+fn many_diff_git_lines_without_headers() {
+    let sample = "diff --git a/x b/x\n".repeat(100_000) + VALID;
+    let patches = Patch::from_multiple(&sample).unwrap();
+    assert_eq!(patches.len(), 100_000);
+    assert!(patches[..99_999].iter().all(|p| p.hunks.is_empty()));
+    assert_eq!(patches[99_999].hunks.len(), 1);
+}
+
+#[test]
+fn diff_git_line_with_many_spaces() {
+    let sample = format!("diff --git a/{} b/x\n{}", " ".repeat(100_000), VALID);
+    let patches = Patch::from_multiple(&sample).unwrap();
+    assert_eq!(patches.len(), 1);
+    assert_eq!(patches[0].git.as_ref().unwrap().new_path, "b/x");
+}
+
+#[test]
+fn enormous_quoted_filename() {
+    let name = "a\\\"".repeat(100_000);
+    let sample = format!(
+        "--- \"{}\"\n{}",
+        name,
+        &VALID[VALID.find('\n').unwrap() + 1..]
+    );
+    let patch = Patch::from_single(&sample).unwrap();
+    assert_eq!(patch.old.path, "a\"".repeat(100_000));
+}
+
+#[test]
+fn enormous_unterminated_quoted_filename() {
+    let name = "a".repeat(1_000_000);
+    let sample = format!(
+        "--- \"{}\n{}",
+        name,
+        &VALID[VALID.find('\n').unwrap() + 1..]
+    );
+    let patch = Patch::from_single(&sample).unwrap();
+    assert_eq!(patch.old.path.len(), name.len() + 1);
+}
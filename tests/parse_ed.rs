@@ -0,0 +1,64 @@
+use patch::{EdCommand, EdCommandKind, EdScript, ParseError};
+
+use pretty_assertions::assert_eq;
+
+#[test]
+fn parse_diff_e_output() -> Result<(), ParseError<'static>> {
+    // Written by `diff -e`
+    let sample = "\
+10d
+4a
+new
+.
+2c
+B
+.
+";
+    let script = EdScript::parse(sample)?;
+    assert_eq!(
+        script.commands,
+        [
+            EdCommand {
+                start: 10,
+                end: 10,
+                kind: EdCommandKind::Delete,
+                lines: vec![],
+            },
+            EdCommand {
+                start: 4,
+                end: 4,
+                kind: EdCommandKind::Append,
+                lines: vec!["new"],
+            },
+            EdCommand {
+                start: 2,
+                end: 2,
+                kind: EdCommandKind::Change,
+                lines: vec!["B"],
+            },
+        ]
+    );
+    assert_eq!(format!("{}\n", script), sample);
+
+    assert_eq!(EdScript::parse("")?, EdScript::default());
+    Ok(())
+}
+
+#[test]
+fn parse_line_of_only_a_dot() -> Result<(), ParseError<'static>> {
+    // `diff -e` writes a `.` line as `..`, then fixes it after the text ends
+    let sample = "\
+3,11d
+1a
+..
+.
+s/.//
+a
+after
+.
+";
+    let script = EdScript::parse(sample)?;
+    assert_eq!(script.commands[1].lines, [".", "after"]);
+    assert_eq!(format!("{}\n", script), sample);
+    Ok(())
+}
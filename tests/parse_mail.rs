@@ -0,0 +1,150 @@
+use chrono::DateTime;
+use patch::{CommitPatch, Line, ParseError};
+
+use pretty_assertions::assert_eq;
+
+#[test]
+fn test_parse_format_patch() -> Result<(), ParseError<'static>> {
+    let sample = "\
+From 961f106701c472f0f3c95609c0b0bf99fb378d98 Mon Sep 17 00:00:00 2001
+From: phil <uniphil@gmail.com>
+Date: Sat, 16 Oct 2021 12:00:00 -0400
+Subject: [PATCH v2 1/3] Fix the greeting so that it is a farewell instead, which is
+ much longer than usual subjects
+
+The old greeting was confusing.
+
+Signed-off-by: phil <uniphil@gmail.com>
+---
+ hello.txt | 2 +-
+ 1 file changed, 1 insertion(+), 1 deletion(-)
+
+diff --git a/hello.txt b/hello.txt
+index ce01362..dd7e1c6 100644
+--- a/hello.txt
++++ b/hello.txt
+@@ -1 +1 @@
+-hello
++goodbye
+-- 
+2.39.5
+
+";
+    let commit = CommitPatch::parse(sample)?;
+    assert_eq!(commit.author, "phil <uniphil@gmail.com>");
+    assert_eq!(
+        commit.date,
+        DateTime::parse_from_rfc3339("2021-10-16T12:00:00-04:00").unwrap()
+    );
+    assert_eq!(
+        commit.subject,
+        "Fix the greeting so that it is a farewell instead, which is much longer than usual \
+        subjects"
+    );
+    assert_eq!(
+        commit.message,
+        "The old greeting was confusing.\n\nSigned-off-by: phil <uniphil@gmail.com>"
+    );
+    assert_eq!(
+        commit.trailers(),
+        [("Signed-off-by", "phil <uniphil@gmail.com>")]
+    );
+    assert_eq!(commit.patches.len(), 1);
+    assert_eq!(
+        commit.patches[0].hunks[0].lines,
+        [Line::Remove("hello"), Line::Add("goodbye")]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parse_cover_letter() -> Result<(), ParseError<'static>> {
+    let sample = "\
+From 961f106701c472f0f3c95609c0b0bf99fb378d98 Mon Sep 17 00:00:00 2001
+From: phil <uniphil@gmail.com>
+Date: Sat, 16 Oct 2021 12:00:00 -0400
+Subject: [PATCH 0/1] Greetings
+
+Some greeting fixes.
+
+phil (1):
+  Fix the greeting
+
+ hello.txt | 2 +-
+ 1 file changed, 1 insertion(+), 1 deletion(-)
+
+-- 
+2.39.5
+";
+    let commit = CommitPatch::parse(sample)?;
+    assert_eq!(commit.subject, "Greetings");
+    assert!(commit.message.starts_with("Some greeting fixes."));
+    // The shortlog isn't made of trailers
+    assert!(commit.trailers().is_empty());
+    assert!(commit.patches.is_empty());
+
+    let err = CommitPatch::parse("From: phil\nSubject: x\n\nno date\n").unwrap_err();
+    assert_eq!(err.line, 1);
+    Ok(())
+}
+
+#[test]
+fn test_parse_mbox() -> Result<(), ParseError<'static>> {
+    let message = "\
+From 961f106701c472f0f3c95609c0b0bf99fb378d98 Mon Sep 17 00:00:00 2001
+From: phil <uniphil@gmail.com>
+Date: Sat, 16 Oct 2021 12:00:00 -0400
+Subject: [PATCH] Fix the greeting
+
+From now on, say goodbye.
+---
+diff --git a/hello.txt b/hello.txt
+--- a/hello.txt
++++ b/hello.txt
+@@ -1 +1 @@
+-hello
++goodbye
+-- 
+2.39.5
+
+";
+    let mbox = message.repeat(3);
+    let series = CommitPatch::from_mbox(&mbox).unwrap();
+    assert_eq!(series.len(), 3);
+    assert!(series
+        .iter()
+        .all(|commit| commit.message == "From now on, say goodbye." && commit.patches.len() == 1));
+
+    // Errors point into the whole mbox, not just the broken message
+    let broken = format!("{}{}", message, message.replace("@@ -1 +1 @@", "@@ -x @@"));
+    let err = CommitPatch::from_mbox(&broken).unwrap_err();
+    assert_eq!(err.line, 27);
+    Ok(())
+}
+
+#[test]
+fn removed_line_like_a_signature() -> Result<(), ParseError<'static>> {
+    // Made with `git format-patch --no-signature`, removing a line `- `
+    let sample = "\
+From 961f106701c472f0f3c95609c0b0bf99fb378d98 Mon Sep 17 00:00:00 2001
+From: phil <uniphil@gmail.com>
+Date: Sat, 16 Oct 2021 12:00:00 -0400
+Subject: [PATCH] Remove the empty list item
+
+---
+diff --git a/list.md b/list.md
+--- a/list.md
++++ b/list.md
+@@ -1,2 +1 @@
+ - one
+-- 
+";
+    let commit = CommitPatch::parse(sample)?;
+    assert_eq!(
+        commit.patches[0].hunks[0].lines,
+        [Line::Context("- one"), Line::Remove("- ")]
+    );
+    let series = CommitPatch::from_mbox(sample)?;
+    assert_eq!(series[0].patches[0].hunks[0].lines.len(), 2);
+    Ok(())
+}
@@ -0,0 +1,350 @@
+use patch::{
+    EdScript, EscapeSet, LimitExceeded, Line, ParseError, ParserOptions, Patch, PatchFileEditor,
+    WarningKind,
+};
+
+use pretty_assertions::assert_eq;
+
+#[test]
+fn gnu_rejects_git_headers_and_junk() {
+    let git = "\
+diff --git a/one.txt b/one.txt
+index 4e3f9d2..8b1c0a7 100644
+--- a/one.txt
++++ b/one.txt
+@@ -1,1 +1,1 @@
+-one
++ONE
+";
+    assert!(Patch::from_single_with_options(git, &ParserOptions::git()).is_ok());
+    assert!(Patch::from_single_with_options(git, &ParserOptions::gnu()).is_err());
+    let options = ParserOptions::gnu().git_headers(true);
+    assert!(Patch::from_single_with_options(git, &options).is_ok());
+
+    let junk = "\
+--- a/one.txt
++++ b/one.txt
+@@ -1,1 +1,1 @@
+-one
++ONE
+Some notes about the next file
+--- a/two.txt
++++ b/two.txt
+@@ -1,1 +1,1 @@
+-two
++TWO
+";
+    assert!(Patch::from_multiple_with_options(junk, &ParserOptions::git()).is_ok());
+    let err = Patch::from_multiple_with_options(junk, &ParserOptions::gnu()).unwrap_err();
+    assert_eq!(err.line, 6);
+}
+
+#[test]
+fn default_rejects_miscounted_hunks() {
+    // The inputs of issues 11, 15 and 17, whose ranges don't include their context line
+    let samples = [
+        "--- old.txt\n+++ new.txt\n@@ -0,0 +0,0 @@ spoopadoop\n x\n",
+        "--- old.txt\r\n+++ new.txt\r\n@@ -0,0 +0,0 @@\r\n x\r\n",
+        "--- unquoted no space\t\n+++ unquoted no space\twith metadata\n@@ -0,0 +0,0 @@\n x\n",
+    ];
+    for sample in samples {
+        assert!(Patch::from_single(sample).is_err(), "{:?}", sample);
+        assert!(Patch::from_multiple(sample).is_err(), "{:?}", sample);
+        let options = ParserOptions::forgiving();
+        assert!(Patch::from_single_with_options(sample, &options).is_ok());
+    }
+}
+
+#[test]
+fn forgiving_ignores_counts() -> Result<(), ParseError<'static>> {
+    // The first hunk has one line more than its ranges say, the second one line less
+    let sample = "\
+--- a/one.txt
++++ b/one.txt
+@@ -1,1 +1,1 @@
+-one
++ONE
++and more
+\\ No newline at end of file
+--- a/two.txt
++++ b/two.txt
+@@ -1,3 +1,3 @@
+-two
++TWO
+
+";
+    assert!(Patch::from_multiple(sample).is_err());
+    let patches = Patch::from_multiple_with_options(sample, &ParserOptions::forgiving())?;
+    assert_eq!(
+        patches[0].hunks[0].lines,
+        [Line::Remove("one"), Line::Add("ONE"), Line::Add("and more")]
+    );
+    assert!(!patches[0].new_end_newline);
+    assert_eq!(
+        patches[1].hunks[0].lines,
+        [Line::Remove("two"), Line::Add("TWO"), Line::Context("")]
+    );
+    // The ranges are kept as written
+    assert_eq!(patches[1].hunks[0].old_range.count, 3);
+
+    let options = ParserOptions::git().strict_counts(false);
+    assert_eq!(
+        Patch::from_multiple_with_options(sample, &options)?,
+        patches
+    );
+    Ok(())
+}
+
+#[test]
+fn forgiving_follows_counts_that_fit() -> Result<(), ParseError<'static>> {
+    // The lines like headers are within the ranges, so they are hunk lines
+    let sample = "\
+--- main.c
++++ main.c
+@@ -1,4 +1,7 @@
++#include<stdio.h>
++
+ int main() {
+ double a;
+--- a;
++++ a;
++printf(\"%d\\n\", a);
+ }
+";
+    let patches = Patch::from_multiple_with_options(sample, &ParserOptions::forgiving())?;
+    assert_eq!(patches, Patch::from_multiple(sample)?);
+    assert_eq!(patches[0].hunks[0].lines[4], Line::Remove("-- a;"));
+    Ok(())
+}
+
+#[test]
+fn strict_no_newline_indicator_is_english_only() -> Result<(), ParseError<'static>> {
+    let sample = "\
+--- a/one.txt
++++ b/one.txt
+@@ -1,1 +1,1 @@
+-one
++ONE
+\\ Pas de fin de ligne à la fin du fichier
+";
+    let patch = Patch::from_single(sample)?;
+    assert!(!patch.new_end_newline);
+
+    let options = ParserOptions::git().strict_no_newline_indicator(true);
+    let err = Patch::from_single_with_options(sample, &options).unwrap_err();
+    assert_eq!(err.line, 6);
+    let english = sample.replace(
+        "Pas de fin de ligne à la fin du fichier",
+        "No newline at end of file",
+    );
+    let patch = Patch::from_single_with_options(&english, &options).unwrap();
+    assert!(!patch.new_end_newline);
+    Ok(())
+}
+
+#[test]
+fn gnu_only_accepts_c_escapes() {
+    let escaped = "\
+--- \"a/caf\\303\\251.txt\"
++++ \"b/caf\\303\\251.txt\"
+@@ -1,1 +1,1 @@
+-a
++b
+";
+    let patch = Patch::from_single_with_options(escaped, &ParserOptions::git()).unwrap();
+    assert_eq!(patch.old.path, "a/café.txt");
+    // Without the octal escapes, the path is read as written
+    let patch = Patch::from_single_with_options(escaped, &ParserOptions::gnu()).unwrap();
+    assert_eq!(patch.old.path, "\"a/caf\\303\\251.txt\"");
+    let options = ParserOptions::gnu().quoted_escapes(EscapeSet::Git);
+    let patch = Patch::from_single_with_options(escaped, &options).unwrap();
+    assert_eq!(patch.old.path, "a/café.txt");
+
+    let quoted = escaped.replace("caf\\303\\251", "my \\\"file\\\"");
+    let patch = Patch::from_single_with_options(&quoted, &ParserOptions::gnu()).unwrap();
+    assert_eq!(patch.new.path, "b/my \"file\".txt");
+}
+
+#[test]
+fn warnings_point_at_the_repairs() -> Result<(), ParseError<'static>> {
+    let sample = "\
+--- a/one.txt
++++ b/one.txt
+@@ -1,2 +1,2 @@
+-one
++ONE
+
+@@ -10,1 +10,1 @@junk
+-ten
++TEN
+";
+    let (_, warnings) = Patch::from_multiple_with_warnings(sample, &ParserOptions::git())?;
+    let found: Vec<_> = warnings
+        .iter()
+        .map(|warning| (warning.kind, warning.span.start_line))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (WarningKind::EmptyContextLine, 6),
+            (WarningKind::TextAfterHunkHeader, 7),
+        ]
+    );
+
+    // With the space put back, only the hunk header is left
+    let (_, warnings) =
+        Patch::from_multiple_with_warnings(&sample.replace("\n\n", "\n \n"), &ParserOptions::git())
+            .unwrap();
+    assert_eq!(warnings.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn extra_hunk_lines_are_told_apart_from_preambles() -> Result<(), ParseError<'static>> {
+    let sample = "\
+--- a/one.txt
++++ b/one.txt
+@@ -1,1 +1,1 @@
+-a
++b
++c
+Some notes about the next file
+--- a/two.txt
++++ b/two.txt
+@@ -1,1 +1,1 @@
+-two
++TWO
+";
+    let (patches, warnings) = Patch::from_multiple_with_warnings(sample, &ParserOptions::git())?;
+    assert_eq!(
+        patches[1].preamble,
+        Some("+c\nSome notes about the next file\n")
+    );
+    let found: Vec<_> = warnings
+        .iter()
+        .map(|warning| (warning.kind, warning.span.start_line, warning.span.end_line))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (WarningKind::ExtraHunkLines, 6, 6),
+            (WarningKind::Preamble, 6, 7),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn limits_stop_parsing() {
+    let sample = "\
+--- a/one.txt
++++ b/one.txt
+@@ -1,2 +1,2 @@
+ one
+-two
++TWO
+@@ -10,1 +10,1 @@
+-ten
++TEN
+--- a/two.txt
++++ b/two.txt
+@@ -1,1 +1,1 @@
+-a much longer line
++A MUCH LONGER LINE
+";
+    let parse = |options: ParserOptions| {
+        Patch::from_multiple_with_options(sample, &options)
+            .map(|patches| patches.len())
+            .map_err(|err| (err.line, err.limit_exceeded))
+    };
+    assert_eq!(
+        parse(ParserOptions::git().max_patches(2).max_hunks(2)),
+        Ok(2)
+    );
+    assert_eq!(
+        parse(ParserOptions::git().max_input_len(100)),
+        Err((1, Some(LimitExceeded::InputLength)))
+    );
+    assert_eq!(
+        parse(ParserOptions::git().max_patches(1)),
+        Err((10, Some(LimitExceeded::Patches)))
+    );
+    assert_eq!(
+        parse(ParserOptions::git().max_hunks(1)),
+        Err((7, Some(LimitExceeded::Hunks)))
+    );
+    assert_eq!(
+        parse(ParserOptions::git().max_hunk_lines(2)),
+        Err((6, Some(LimitExceeded::HunkLines)))
+    );
+    assert_eq!(
+        parse(ParserOptions::git().max_line_len(10)),
+        Err((13, Some(LimitExceeded::LineLength)))
+    );
+
+    let err =
+        Patch::from_single_with_options(sample, &ParserOptions::git().max_hunks(1)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 7: Limit exceeded: too many hunks in a patch"
+    );
+
+    for options in [
+        ParserOptions::git().max_input_len(100),
+        ParserOptions::git().max_patches(1),
+        ParserOptions::git().max_hunks(1),
+        ParserOptions::git().max_hunk_lines(2),
+        ParserOptions::git().max_line_len(10),
+    ] {
+        let expected = parse(options);
+        let scanned = Patch::scan_headers_with_options(sample, &options)
+            .map(|patches| patches.len())
+            .map_err(|err| (err.line, err.limit_exceeded));
+        assert_eq!(scanned, expected);
+        let iterated = Patch::iter_multiple_with_options(sample, &options)
+            .find_map(Result::err)
+            .map(|err| (err.line, err.limit_exceeded));
+        assert_eq!(Err(iterated.unwrap()), expected);
+    }
+
+    // The other ways to parse patches stop at the same place
+    let options = ParserOptions::git().max_patches(1);
+    let expected = Some((10, Some(LimitExceeded::Patches)));
+    let line = |err: &ParseError<'_>| (err.line, err.limit_exceeded);
+    let err = Patch::from_multiple_with_spans_with_options(sample, &options).unwrap_err();
+    assert_eq!(Some(line(&err)), expected);
+    let err = PatchFileEditor::with_options(sample, &options).unwrap_err();
+    assert_eq!(Some(line(&err)), expected);
+    let err = Patch::from_recursive_diff_with_options(sample, &options).unwrap_err();
+    assert_eq!(Some(line(&err)), expected);
+    let mut output = sample.as_bytes().to_vec();
+    let err = Patch::from_git_diff_output_with_options(&mut output, &options).unwrap_err();
+    assert_eq!(Some(line(&err)), expected);
+    let (patches, error) = Patch::from_multiple_partial_with_options(sample, &options);
+    assert_eq!((patches.len(), error.as_ref().map(line)), (1, expected));
+    let (patches, errors) = Patch::from_multiple_lossy_with_options(sample, &options);
+    assert_eq!(patches.len(), 1);
+    assert_eq!(errors.len(), 1);
+    assert_eq!((errors[0].0.start_line, errors[0].0.end_line), (10, 14));
+    assert_eq!(Some(line(&errors[0].1)), expected);
+}
+
+#[test]
+fn numbers_too_large_are_errors() {
+    let huge = "99999999999999999999";
+    let hunk = format!("--- a\n+++ b\n@@ -1,{} +1 @@\n-a\n+b\n", huge);
+    assert_eq!(Patch::from_single(&hunk).unwrap_err().line, 3);
+    let hunk = format!("--- a\n+++ b\n@@ -{} +1 @@\n-a\n+b\n", huge);
+    assert_eq!(Patch::from_multiple(&hunk).unwrap_err().line, 3);
+
+    let ed = format!("{}d\n", huge);
+    assert_eq!(EdScript::parse(&ed).unwrap_err().line, 1);
+    let normal = format!("1,{}d0\n< a\n", u64::MAX);
+    assert!(Patch::from_normal(&normal, "a", "b").is_err());
+
+    let binary = format!(
+        "diff --git a/x b/x\nindex 0000000..e69de29 100644\nGIT binary patch\nliteral {}\nHcmV?d00001\n\nliteral 0\nHcmV?d00001\n\n",
+        huge
+    );
+    assert!(Patch::from_multiple(&binary).is_err());
+}
@@ -1,5 +1,9 @@
 use chrono::DateTime;
-use patch::{File, FileMetadata, ParseError, Patch};
+use patch::{
+    verify_roundtrip, CombinedLine, CombinedPatch, DiffEntry, File, FileChangeKind, FileMetadata,
+    GitMetadata, Hunk, IncompleteFileError, Line, LineEnding, ParseError, Patch, PatchFileEditor,
+    PropertyChangeKind, Range, Span, SubmoduleChange,
+};
 
 use pretty_assertions::assert_eq;
 
@@ -21,6 +25,7 @@ fn test_parse() -> Result<(), ParseError<'static>> {
         patch.old,
         File {
             path: "before.py".into(),
+            escaped: false,
             meta: None
         }
     );
@@ -28,10 +33,11 @@ fn test_parse() -> Result<(), ParseError<'static>> {
         patch.new,
         File {
             path: "after.py".into(),
+            escaped: false,
             meta: None
         }
     );
-    assert!(patch.end_newline);
+    assert!(patch.old_end_newline && patch.new_end_newline);
 
     assert_eq!(format!("{}\n", patch), sample);
 
@@ -57,6 +63,7 @@ fn test_parse_no_newline_indicator() -> Result<(), ParseError<'static>> {
         patch.old,
         File {
             path: "before.py".into(),
+            escaped: false,
             meta: None
         }
     );
@@ -64,10 +71,11 @@ fn test_parse_no_newline_indicator() -> Result<(), ParseError<'static>> {
         patch.new,
         File {
             path: "after.py".into(),
+            escaped: false,
             meta: None
         }
     );
-    assert!(!patch.end_newline);
+    assert!(!patch.old_end_newline && !patch.new_end_newline);
 
     assert_eq!(format!("{}\n", patch), sample);
 
@@ -93,6 +101,7 @@ fn test_parse_timestamps() -> Result<(), ParseError<'static>> {
         patch.old,
         File {
             path: "before.py".into(),
+            escaped: false,
             meta: Some(FileMetadata::DateTime(
                 DateTime::parse_from_rfc3339("2002-02-21T23:30:39.942229878-08:00").unwrap()
             )),
@@ -102,12 +111,13 @@ fn test_parse_timestamps() -> Result<(), ParseError<'static>> {
         patch.new,
         File {
             path: "after.py".into(),
+            escaped: false,
             meta: Some(FileMetadata::DateTime(
                 DateTime::parse_from_rfc3339("2002-02-21T23:30:50-08:00").unwrap()
             )),
         }
     );
-    assert!(!patch.end_newline);
+    assert!(!patch.old_end_newline && !patch.new_end_newline);
 
     // to_string() uses Display but adds no trailing newline
     assert_eq!(patch.to_string(), sample);
@@ -133,6 +143,7 @@ fn test_parse_other() -> Result<(), ParseError<'static>> {
         patch.old,
         File {
             path: "before.py".into(),
+            escaped: false,
             meta: Some(FileMetadata::Other(
                 "08f78e0addd5bf7b7aa8887e406493e75e8d2b55".into()
             )),
@@ -142,12 +153,13 @@ fn test_parse_other() -> Result<(), ParseError<'static>> {
         patch.new,
         File {
             path: "after.py".into(),
+            escaped: false,
             meta: Some(FileMetadata::Other(
                 "e044048282ce75186ecc7a214fd3d9ba478a2816".into()
             )),
         }
     );
-    assert!(patch.end_newline);
+    assert!(patch.old_end_newline && patch.new_end_newline);
 
     assert_eq!(format!("{}\n", patch), sample);
 
@@ -172,6 +184,7 @@ fn test_parse_escaped() -> Result<(), ParseError<'static>> {
         patch.old,
         File {
             path: "before.py".into(),
+            escaped: false,
             meta: Some(FileMetadata::Other("asdf \\ \n \t \0 \r \" ".into())),
         }
     );
@@ -179,12 +192,13 @@ fn test_parse_escaped() -> Result<(), ParseError<'static>> {
         patch.new,
         File {
             path: "My Work/after.py".into(),
+            escaped: false,
             meta: Some(FileMetadata::Other(
                 "My project is cool! Wow!!; SELECT * FROM USERS;".into()
             )),
         }
     );
-    assert!(patch.end_newline);
+    assert!(patch.old_end_newline && patch.new_end_newline);
 
     assert_eq!(format!("{}\n", patch), sample);
 
@@ -193,9 +207,8 @@ fn test_parse_escaped() -> Result<(), ParseError<'static>> {
 
 #[test]
 fn test_parse_triple_plus_minus() -> Result<(), ParseError<'static>> {
-    // Our parser has some hacky rules to make sure that lines starting with +++ or --- aren't
-    // interpreted as regular addition/removal lines that could be part of a hunk. This test checks
-    // to make sure that code is working.
+    // Lines starting with +++ or --- inside a hunk are regular addition/removal lines, not the
+    // headers of a new patch
     let sample = r#"--- main.c
 +++ main.c
 @@ -1,4 +1,7 @@
@@ -216,6 +229,7 @@ fn test_parse_triple_plus_minus() -> Result<(), ParseError<'static>> {
         patch.old,
         File {
             path: "main.c".into(),
+            escaped: false,
             meta: None
         }
     );
@@ -223,10 +237,11 @@ fn test_parse_triple_plus_minus() -> Result<(), ParseError<'static>> {
         patch.new,
         File {
             path: "main.c".into(),
+            escaped: false,
             meta: None
         }
     );
-    assert!(patch.end_newline);
+    assert!(patch.old_end_newline && patch.new_end_newline);
 
     assert_eq!(patch.hunks.len(), 1);
     assert_eq!(patch.hunks[0].lines.len(), 8);
@@ -236,18 +251,11 @@ fn test_parse_triple_plus_minus() -> Result<(), ParseError<'static>> {
     Ok(())
 }
 
-//FIXME: This test should NOT panic. When we have more sophisticated chunk line parsing that
-// actually takes the hunk ranges into account, the #[should_panic] annotation should be removed.
-// See the FIXME comment on top of the chunk_line parser.
 #[test]
-#[should_panic]
 fn test_parse_triple_plus_minus_hack() {
-    // Our parser has some hacky rules to make sure that lines starting with +++ or --- aren't
-    // interpreted as regular addition/removal lines that could be part of a hunk. This test
-    // demonstrates that these rules are not foolproof. The only differences between this test and
-    // test_parse_triple_plus_minus are `--- a` and `+++ a` vs `---a` and `+++a`. In either case,
-    // we should be able to determine that those lines do not start a new patch based on the ranges
-    // provided for the hunk.
+    // The only differences between this test and test_parse_triple_plus_minus are `--- a` and
+    // `+++ a` vs `---a` and `+++a`. These look exactly like patch headers, so the ranges provided
+    // for the hunk are what tell us that those lines do not start a new patch.
     let sample = r#"--- main.c
 +++ main.c
 @@ -1,4 +1,7 @@
@@ -268,6 +276,7 @@ fn test_parse_triple_plus_minus_hack() {
         patch.old,
         File {
             path: "main.c".into(),
+            escaped: false,
             meta: None
         }
     );
@@ -275,13 +284,1119 @@ fn test_parse_triple_plus_minus_hack() {
         patch.new,
         File {
             path: "main.c".into(),
+            escaped: false,
             meta: None
         }
     );
-    assert!(patch.end_newline);
+    assert!(patch.old_end_newline && patch.new_end_newline);
 
     assert_eq!(patch.hunks.len(), 1);
     assert_eq!(patch.hunks[0].lines.len(), 8);
 
     assert_eq!(format!("{}\n", patch), sample);
 }
+
+#[test]
+fn test_parse_git_metadata() -> Result<(), ParseError<'static>> {
+    let sample = "\
+diff --git a/script.sh b/script.sh
+old mode 100644
+new mode 100755
+index c7921f5..8946660
+--- a/script.sh
++++ b/script.sh
+@@ -1,1 +1,1 @@
+-echo hello
++echo goodbye
+diff --git a/modified_file b/modified_file
+index c7921f5..8946660 100644
+--- a/modified_file
++++ b/modified_file
+@@ -1,1 +1,1 @@
+-This should be updated.
++This is now updated.
+";
+    let patches = Patch::from_multiple(sample)?;
+    assert_eq!(
+        patches[0].git,
+        Some(GitMetadata {
+            old_path: "a/script.sh".into(),
+            new_path: "b/script.sh".into(),
+            escaped_paths: false,
+            old_hash: Some("c7921f5"),
+            new_hash: Some("8946660"),
+            old_mode: Some(0o100644),
+            new_mode: Some(0o100755),
+            kind: FileChangeKind::Modified,
+            similarity: None,
+            dissimilarity: None,
+            operation: None,
+        })
+    );
+    assert_eq!(
+        patches[1].git,
+        Some(GitMetadata {
+            old_path: "a/modified_file".into(),
+            new_path: "b/modified_file".into(),
+            escaped_paths: false,
+            old_hash: Some("c7921f5"),
+            new_hash: Some("8946660"),
+            old_mode: Some(0o100644),
+            new_mode: Some(0o100644),
+            kind: FileChangeKind::Modified,
+            similarity: None,
+            dissimilarity: None,
+            operation: None,
+        })
+    );
+
+    let output: String = patches.iter().map(|p| format!("{}\n", p)).collect();
+    assert_eq!(output, sample);
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_fragments() -> Result<(), ParseError<'static>> {
+    let hunk = Hunk::parse("@@ -1 +1 @@\n-bacon\n+python\n")?;
+    assert_eq!(hunk.old_range, Range { start: 1, count: 1 });
+    assert_eq!(hunk.lines, [Line::Remove("bacon"), Line::Add("python")]);
+
+    // Anything that isn't part of the hunk is an error rather than silently ignored
+    assert!(Hunk::parse("@@ -1 +1 @@\n-bacon\n+python\ngarbage\n").is_err());
+
+    let file = File::parse_header_line("--- \"My Work/before.py\"")?;
+    assert_eq!(
+        file,
+        File {
+            path: "My Work/before.py".into(),
+            escaped: false,
+            meta: None,
+        }
+    );
+    assert!(File::parse_header_line("before.py").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_git_created_deleted() -> Result<(), ParseError<'static>> {
+    let sample = "\
+diff --git a/added_file b/added_file
+new file mode 100644
+index 0000000..9b710f3
+--- /dev/null
++++ b/added_file
+@@ -0,0 +1,1 @@
++This was missing!
+diff --git a/removed_file b/removed_file
+deleted file mode 100755
+index 1f38447..0000000
+--- a/removed_file
++++ /dev/null
+@@ -1,1 +0,0 @@
+-This content shouldn't be here.
+diff --git a/modified_file b/modified_file
+index c7921f5..8946660 100644
+--- a/modified_file
++++ b/modified_file
+@@ -1,1 +1,1 @@
+-This should be updated.
++This is now updated.
+";
+    let patches = Patch::from_multiple(sample)?;
+    assert_eq!(patches[0].kind(), FileChangeKind::Created);
+    assert_eq!(patches[0].git.as_ref().unwrap().old_mode, None);
+    assert_eq!(patches[0].git.as_ref().unwrap().new_mode, Some(0o100644));
+    assert_eq!(patches[1].kind(), FileChangeKind::Deleted);
+    assert_eq!(patches[1].git.as_ref().unwrap().old_mode, Some(0o100755));
+    assert_eq!(patches[1].git.as_ref().unwrap().new_mode, None);
+    assert_eq!(patches[2].kind(), FileChangeKind::Modified);
+
+    let output: String = patches.iter().map(|p| format!("{}\n", p)).collect();
+    assert_eq!(output, sample);
+
+    Ok(())
+}
+
+#[test]
+fn test_dev_null_created_deleted() -> Result<(), ParseError<'static>> {
+    let sample = "\
+--- /dev/null
++++ added_file
+@@ -0,0 +1,1 @@
++This was missing!
+--- a/removed_file
++++ b//dev/null
+@@ -1,1 +0,0 @@
+-This content shouldn't be here.
+";
+    let patches = Patch::from_multiple(sample)?;
+    assert!(patches[0].old.is_null());
+    assert!(patches[0].is_creation());
+    assert!(!patches[0].is_deletion());
+    assert!(patches[1].new.is_null());
+    assert!(patches[1].is_deletion());
+    assert!(!File::parse_header_line("--- dev/null")?.is_null());
+    Ok(())
+}
+
+#[test]
+fn test_parse_git_diff_output() {
+    let mut output = b"\
+commit 8b9de2b5c2f4b8a1b2b7e1f1d3a4c5e6f7a8b9c0
+Author: phil <uniphil@gmail.com>
+Date:   Sat Oct 16 12:00:00 2021 -0400
+
+    Rename and fix the greeting
+
+diff --git a/hello.txt b/greeting.txt
+similarity index 50%
+rename from hello.txt
+rename to greeting.txt
+index c7921f5..8946660 100644
+--- a/hello.txt
++++ b/greeting.txt
+@@ -1,2 +1,2 @@
+ hello
+-world
++there
+
+commit 0bc302b2c6b79b3e5a7b1e2d03c8aa5a2e3f2d1c
+Author: phil <uniphil@gmail.com>
+Date:   Fri Oct 15 12:00:00 2021 -0400
+
+    Empty commit
+"
+    .to_vec();
+    let patches = Patch::from_git_diff_output(&mut output).unwrap();
+    assert_eq!(patches.len(), 1);
+    assert_eq!(patches[0].new.path, "b/greeting.txt");
+    assert_eq!(patches[0].hunks[0].lines.len(), 3);
+
+    assert!(Patch::from_git_diff_output(&mut []).unwrap().is_empty());
+    let mut empty_commit = b"commit 0bc302b\n\n    Empty\n".to_vec();
+    assert!(Patch::from_git_diff_output(&mut empty_commit)
+        .unwrap()
+        .is_empty());
+
+    let mut no_hunks = b"diff --git a/x b/x\n--- a/x\n+++ b/x\n".to_vec();
+    let err = Patch::from_git_diff_output(&mut no_hunks).unwrap_err();
+    assert_eq!(err.line, 4);
+
+    let mut invalid_utf8 = b"--- a/x\n+++ b/x\n@@ -1 +1 @@\n-\xe9t\xe9\n".to_vec();
+    let err = Patch::from_git_diff_output(&mut invalid_utf8).unwrap_err();
+    assert_eq!((err.line, err.offset), (4, 29));
+
+    // More hunk lines than the counts say, before the next commit or at the end
+    let mut too_many_lines =
+        b"--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+b\n+c\n\ncommit 0bc302b\n".to_vec();
+    let err = Patch::from_git_diff_output(&mut too_many_lines).unwrap_err();
+    assert_eq!((err.line, err.fragment), (6, "+c"));
+    let mut too_many_lines = b"--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+b\n+c\n".to_vec();
+    assert_eq!(
+        Patch::from_git_diff_output(&mut too_many_lines)
+            .unwrap_err()
+            .line,
+        6
+    );
+
+    // Output of `git diff --color=always`
+    let mut colored = b"\
+\x1b[1mdiff --git a/x b/x\x1b[m
+\x1b[1mindex c7921f5..8946660 100644\x1b[m
+\x1b[1m--- a/x\x1b[m
+\x1b[1m+++ b/x\x1b[m
+\x1b[36m@@ -1 +1 @@\x1b[m
+\x1b[31m-a\x1b[m
+\x1b[32m+b\x1b[m\x1b[41m \x1b[m
+"
+    .to_vec();
+    let patches = Patch::from_git_diff_output(&mut colored).unwrap();
+    assert_eq!(
+        patches[0].hunks[0].lines,
+        [Line::Remove("a"), Line::Add("b ")]
+    );
+    assert_eq!(patches[0].git.as_ref().unwrap().old_hash, Some("c7921f5"));
+}
+
+#[test]
+fn test_parse_combined_diff() -> Result<(), ParseError<'static>> {
+    let sample = "\
+commit 3f1e2a9
+Merge: 5d0c1f4 8a2b7e3
+
+    Merge branch 'side'
+
+diff --cc f.txt
+index f04eb26,ddc897f..571375f
+--- a/f.txt
++++ b/f.txt
+@@@ -1,3 -1,3 +1,4 @@@
+  one
+- 2
+ -TWO
+++two-ish
+  three
+++four
+";
+    let patches = CombinedPatch::from_multiple(sample)?;
+    assert_eq!(patches.len(), 1);
+    let patch = &patches[0];
+    assert_eq!(patch.path, "f.txt");
+    assert!(patch.dense);
+    assert_eq!(patch.old_hashes, ["f04eb26", "ddc897f"]);
+    assert_eq!(patch.new_hash, Some("571375f"));
+    assert_eq!(patch.new.path, "b/f.txt");
+
+    let hunk = &patch.hunks[0];
+    assert_eq!(
+        hunk.old_ranges,
+        [Range { start: 1, count: 3 }, Range { start: 1, count: 3 }]
+    );
+    assert_eq!(hunk.new_range, Range { start: 1, count: 4 });
+    assert_eq!(hunk.lines.len(), 6);
+    assert_eq!(
+        hunk.lines[2],
+        CombinedLine {
+            markers: " -",
+            text: "TWO"
+        }
+    );
+
+    let formatted = format!("{}\n", patch);
+    assert_eq!(formatted, &sample[sample.find("diff --cc").unwrap()..]);
+    assert_eq!(CombinedPatch::from_multiple(&formatted).unwrap(), patches);
+
+    // Text after the last patch is an error, not a panic
+    let trailing = format!("{}foo\n", formatted);
+    let err = CombinedPatch::from_multiple(&trailing).unwrap_err();
+    assert_eq!(err.line, 12);
+    Ok(())
+}
+
+#[test]
+fn test_parse_submodule() -> Result<(), ParseError<'static>> {
+    let sample = "\
+diff --git a/sub b/sub
+new file mode 160000
+index 0000000..032a065
+--- /dev/null
++++ b/sub
+@@ -0,0 +1 @@
++Subproject commit 032a065a41b6ef01731b0215289633cdac04b53f
+diff --git a/sub b/sub
+index 032a065..f17ae85 160000
+--- a/sub
++++ b/sub
+@@ -1 +1 @@
+-Subproject commit 032a065a41b6ef01731b0215289633cdac04b53f
++Subproject commit f17ae851a67e84a0748a47d16a7c7977e8b1eff4-dirty
+diff --git a/notes.txt b/notes.txt
+index 032a065..f17ae85 100644
+--- a/notes.txt
++++ b/notes.txt
+@@ -1 +1 @@
+-Subproject commit 032a065a41b6ef01731b0215289633cdac04b53f
++Subproject commit f17ae851a67e84a0748a47d16a7c7977e8b1eff4
+";
+    let patches = Patch::from_multiple(sample)?;
+    assert_eq!(
+        patches[0].submodule(),
+        Some(SubmoduleChange {
+            old_commit: None,
+            new_commit: Some("032a065a41b6ef01731b0215289633cdac04b53f"),
+        })
+    );
+    assert_eq!(
+        patches[1].submodule(),
+        Some(SubmoduleChange {
+            old_commit: Some("032a065a41b6ef01731b0215289633cdac04b53f"),
+            new_commit: Some("f17ae851a67e84a0748a47d16a7c7977e8b1eff4"),
+        })
+    );
+    // Not a submodule, even though the content looks like one
+    assert_eq!(patches[2].submodule(), None);
+    Ok(())
+}
+
+#[test]
+fn test_whole_file_text() -> Result<(), ParseError<'static>> {
+    let sample = "\
+diff --git a/hello.txt b/hello.txt
+deleted file mode 100644
+index 8946660..0000000
+--- a/hello.txt
++++ b/hello.txt
+@@ -1,2 +0,0 @@
+-hello
+-world
+\\ No newline at end of file
+";
+    let patch = Patch::from_single(sample)?;
+    assert_eq!(patch.old_text(), Ok("hello\nworld".to_string()));
+    assert_eq!(patch.new_text(), Ok(String::new()));
+
+    let sample = "\
+--- a/hello.txt
++++ b/hello.txt
+@@ -3,2 +3,2 @@
+ hello
+-world
++there
+@@ -9,1 +9,2 @@
+ bye
++now
+";
+    let patch = Patch::from_single(sample)?;
+    let err = patch.new_text().unwrap_err();
+    assert_eq!(
+        err,
+        IncompleteFileError {
+            gaps: vec![Range { start: 1, count: 2 }, Range { start: 5, count: 4 }],
+            unknown_after: Some(11),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Patch doesn't contain the whole file: lines 1-2 are missing, lines 5-8 are missing, \
+        lines from 11 on may be missing"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_strip_prefixes() -> Result<(), ParseError<'static>> {
+    let sample = "\
+diff --git a/added_file b/added_file
+new file mode 100644
+index 0000000..9b710f3
+--- /dev/null
++++ b/added_file
+@@ -0,0 +1,1 @@
++This was missing!
+diff --git i/My Work/notes.txt w/My Work/notes.txt
+--- \"i/My Work/notes.txt\"
++++ \"w/My Work/notes.txt\"
+@@ -1,1 +1,1 @@
+-old
++new
+diff --git a/file b/file
+--- a/file
++++ a/file
+@@ -1,1 +1,1 @@
+-old
++new
+";
+    let mut patches = Patch::from_multiple(sample)?;
+    for patch in &mut patches {
+        patch.strip_prefixes();
+    }
+    assert_eq!(patches[0].old.path, "/dev/null");
+    assert_eq!(patches[0].new.path, "added_file");
+    assert_eq!(patches[0].git.as_ref().unwrap().old_path, "added_file");
+    assert_eq!(patches[1].old.path, "My Work/notes.txt");
+    assert_eq!(patches[1].new.path, "My Work/notes.txt");
+    // Looks like `git diff --no-prefix` in a directory named `a`
+    assert_eq!(patches[2].old.path, "a/file");
+    assert_eq!(patches[2].new.path, "a/file");
+    Ok(())
+}
+
+#[test]
+fn test_parse_multiple_with_spans() -> Result<(), ParseError<'static>> {
+    let sample = "\
+commit 0bc302b2c6b79b3e5a7b1e2d03c8aa5a2e3f2d1c
+
+diff --git a/old_name.txt b/new_name.txt
+similarity index 100%
+rename from old_name.txt
+rename to new_name.txt
+diff --git a/hello.txt b/hello.txt
+--- a/hello.txt
++++ b/hello.txt
+@@ -1 +1 @@
+-hello
++goodbye
+\\ No newline at end of file
+
+";
+    let patches = Patch::from_multiple_with_spans(sample)?;
+    let spans: Vec<Span> = patches.iter().map(|(_, span)| *span).collect();
+    assert_eq!(
+        spans,
+        [
+            Span {
+                start: 49,
+                end: 160,
+                start_line: 3,
+                end_line: 6,
+            },
+            Span {
+                start: 160,
+                end: 283,
+                start_line: 7,
+                end_line: 13,
+            },
+        ]
+    );
+    assert!(sample[spans[0].start..spans[0].end].starts_with("diff --git a/old_name.txt"));
+    assert!(sample[spans[1].start..spans[1].end].ends_with("end of file\n"));
+    Ok(())
+}
+
+#[test]
+fn test_no_newline_per_side() -> Result<(), ParseError<'static>> {
+    // Only the old file is missing its newline
+    let sample = "\
+--- a/f.txt
++++ b/f.txt
+@@ -1,2 +1,3 @@
+ one
+-two
+\\ No newline at end of file
++two
++three
+";
+    let patch = Patch::from_single(sample)?;
+    assert!(!patch.old_end_newline);
+    assert!(patch.new_end_newline);
+    assert_eq!(patch.hunks[0].lines.len(), 4);
+    assert_eq!(format!("{}\n", patch), sample);
+
+    // Neither file ends in a newline, but their last lines differ
+    let sample = "\
+--- a/f.txt
++++ b/f.txt
+@@ -1,1 +1,1 @@
+-one
+\\ No newline at end of file
++two
+\\ No newline at end of file
+";
+    let patch = Patch::from_single(sample)?;
+    assert!(!patch.old_end_newline);
+    assert!(!patch.new_end_newline);
+    assert_eq!(format!("{}\n", patch), sample);
+
+    // The indicator can't follow a line that isn't the last of its side
+    let sample = "\
+--- a/f.txt
++++ b/f.txt
+@@ -1,2 +1,2 @@
+-one
+\\ No newline at end of file
+-two
++three
++four
+";
+    assert!(Patch::from_single(sample).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_whole_file_text_without_newline() -> Result<(), ParseError<'static>> {
+    let sample = "\
+--- /dev/null
++++ b/hello.txt
+@@ -0,0 +1,2 @@
++hello
++world
+\\ No newline at end of file
+";
+    let patch = Patch::from_single(sample)?;
+    assert_eq!(patch.new_text(), Ok("hello\nworld".to_string()));
+    Ok(())
+}
+
+#[test]
+fn test_preamble() -> Result<(), ParseError<'static>> {
+    let sample = "\
+Fix the greeting
+
+The old one was confusing.
+---
+ hello.txt | 2 +-
+diff --git a/hello.txt b/hello.txt
+--- a/hello.txt
++++ b/hello.txt
+@@ -1,1 +1,1 @@
+-hello
++goodbye
+Second patch:
+--- a/bye.txt
++++ b/bye.txt
+@@ -1,1 +1,1 @@
+-bye
++hello
+";
+    let patches = Patch::from_multiple(sample)?;
+    assert_eq!(
+        patches[0].preamble,
+        Some("Fix the greeting\n\nThe old one was confusing.\n---\n hello.txt | 2 +-\n")
+    );
+    assert_eq!(patches[1].preamble, Some("Second patch:\n"));
+
+    let printed: String = patches.iter().map(|p| format!("{}\n", p)).collect();
+    assert_eq!(printed, sample);
+
+    let patch = Patch::from_single("--- a\n+++ b\n@@ -1,1 +1,1 @@\n-a\n+b\n")?;
+    assert_eq!(patch.preamble, None);
+    Ok(())
+}
+
+#[test]
+fn test_preamble_after_metadata_only_patch() -> Result<(), ParseError<'static>> {
+    // `git log -p` output, where the commit after a mode change isn't part of its header
+    let sample = "\
+commit 5d0c1f4
+Author: phil <uniphil@gmail.com>
+
+    Make the script executable
+
+diff --git a/run.sh b/run.sh
+old mode 100644
+new mode 100755
+
+commit 3f1e2a9
+Author: phil <uniphil@gmail.com>
+
+    Fix the greeting
+
+diff --git a/hello.txt b/hello.txt
+--- a/hello.txt
++++ b/hello.txt
+@@ -1,1 +1,1 @@
+-hello
++goodbye
+";
+    let patches = Patch::from_multiple(sample)?;
+    assert_eq!(patches.len(), 2);
+    assert_eq!(
+        patches[1].preamble,
+        Some("commit 3f1e2a9\nAuthor: phil <uniphil@gmail.com>\n\n    Fix the greeting\n\n")
+    );
+    assert!(verify_roundtrip(sample)?.is_lossless());
+    Ok(())
+}
+
+#[test]
+fn test_parse_multiple_lossy() {
+    let sample = "\
+diff --git a/one.txt b/one.txt
+--- a/one.txt
++++ b/one.txt
+@@ -1,1 +1,1 @@
+-one
++ONE
+diff --git a/two.txt b/two.txt
+--- a/two.txt
++++ b/two.txt
+@@ -1,3 +1,3 @@
+-two
++TWO
+diff --git a/three.txt b/three.txt
+--- a/three.txt
++++ b/three.txt
+@@ -1,1 +1,1 @@
+-three
++THREE
+diff --git a/four.txt b/four.txt
+--- a/four.txt
+";
+    let (patches, errors) = Patch::from_multiple_lossy(sample);
+    let paths: Vec<&str> = patches.iter().map(|p| &*p.new.path).collect();
+    assert_eq!(paths, ["b/one.txt", "b/three.txt"]);
+
+    let lines: Vec<(u32, u32)> = errors
+        .iter()
+        .map(|(span, _)| (span.start_line, span.end_line))
+        .collect();
+    assert_eq!(lines, [(7, 12), (19, 20)]);
+    // The error points into the broken patch
+    assert_eq!(errors[0].1.line, 10);
+
+    // Text that isn't a patch is reported too, e.g. hunk lines beyond the counts
+    let (patches, errors) = Patch::from_multiple_lossy("no patches here\n");
+    assert!(patches.is_empty());
+    assert_eq!(errors.len(), 1);
+    assert_eq!((errors[0].0.start_line, errors[0].0.end_line), (1, 1));
+    let extra_line = format!(
+        "{}+c\n",
+        &sample[..sample.find("diff --git a/two").unwrap()]
+    );
+    let (patches, errors) = Patch::from_multiple_lossy(&extra_line);
+    assert_eq!(patches.len(), 1);
+    assert_eq!((errors[0].0.start_line, errors[0].0.end_line), (7, 7));
+    assert_eq!((errors[0].1.line, errors[0].1.fragment), (7, "+c"));
+}
+
+#[test]
+fn test_carriage_returns_in_lines() -> Result<(), ParseError<'static>> {
+    // A CRLF file in a patch with LF line endings, plus a lone `\r` inside a line
+    let sample = "\
+--- before.txt
++++ after.txt
+@@ -1,2 +1,2 @@
+-bacon\r
++py\rthon\r
+ guido\n";
+    let patch = Patch::from_single(sample)?;
+    assert_eq!(patch.line_ending, LineEnding::Lf);
+    assert_eq!(
+        patch.hunks[0].lines,
+        [
+            Line::Remove("bacon\r"),
+            Line::Add("py\rthon\r"),
+            Line::Context("guido"),
+        ]
+    );
+    assert!(patch.hunks[0].has_mixed_line_endings());
+    assert_eq!(patch.to_string(), sample.trim_end_matches('\n'));
+
+    // In a CRLF patch, the line endings belong to the patch
+    let crlf = sample.replace('\n', "\r\n");
+    let patch = Patch::from_single(&crlf).unwrap();
+    assert_eq!(patch.hunks[0].lines[0], Line::Remove("bacon\r"));
+    assert_eq!(patch.hunks[0].lines[2], Line::Context("guido"));
+    Ok(())
+}
+
+#[test]
+fn test_parse_multiple_partial() {
+    let sample = "\
+diff --git a/one.txt b/one.txt
+--- a/one.txt
++++ b/one.txt
+@@ -1,1 +1,1 @@
+-one
++ONE
+diff --git a/two.txt b/two.txt
+--- a/two.txt
++++ b/two.txt
+@@ -1,1 +1,1 @@
+-two
++TWO
+";
+    let (patches, error) = Patch::from_multiple_partial(sample);
+    assert_eq!(patches.len(), 2);
+    assert!(error.is_none());
+
+    // Cut off in the middle of the second patch's hunk
+    let truncated = &sample[..sample.len() - "+TWO\n".len()];
+    let (patches, error) = Patch::from_multiple_partial(truncated);
+    assert_eq!(patches.len(), 1);
+    assert_eq!(patches[0].new.path, "b/one.txt");
+    // The error points at the hunk that was cut off
+    assert_eq!(error.unwrap().line, 10);
+
+    // Extra hunk lines are reported where they start, not at the end of the input
+    let extra_line = format!("{}+c\n", sample);
+    let (patches, error) = Patch::from_multiple_partial(&extra_line);
+    assert_eq!(patches.len(), 2);
+    let error = error.unwrap();
+    assert_eq!(
+        (error.line, error.offset, error.fragment),
+        (13, sample.len(), "+c")
+    );
+
+    let (patches, error) = Patch::from_multiple_partial("");
+    assert!(patches.is_empty());
+    assert!(error.is_some());
+}
+
+#[test]
+fn test_iter_multiple_error_location() {
+    let sample = "--- a/x\n+++ b/x\n@@ -1,1 +1,1 @@\n-a\n+b\n+c\n";
+    let iterated: Vec<_> = Patch::iter_multiple(sample).collect();
+    assert_eq!(iterated.len(), 2);
+    assert!(iterated[0].is_ok());
+    // The extra hunk line is reported where it starts, not at the end of the input
+    let error = iterated[1].as_ref().unwrap_err();
+    assert_eq!((error.line, error.offset, error.fragment), (6, 38, "+c"));
+
+    let error = Patch::iter_multiple("--- a/x\n+++ b/x\n@@ -1,x +1 @@\n")
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.line, 3);
+}
+
+#[test]
+fn test_comments_between_patches() -> Result<(), ParseError<'static>> {
+    let sample = "\
+# Change one
+--- a/one.txt
++++ b/one.txt
+@@ -1,1 +1,1 @@
+-one
++ONE
+#Change two
+#   indented
+--- a/two.txt
++++ b/two.txt
+@@ -1,1 +1,1 @@
+-two
++TWO
+";
+    let mut patches = Patch::from_multiple(sample)?;
+    assert_eq!(patches[0].comments(), ["Change one"]);
+    assert_eq!(patches[1].comments(), ["Change two", "  indented"]);
+
+    // Rewriting a patch keeps its comments
+    patches[1].hunks[0].lines[1] = Line::Add("2");
+    assert!(patches[1]
+        .to_string()
+        .starts_with("#Change two\n#   indented\n--- a/two.txt\n"));
+    Ok(())
+}
+
+#[test]
+fn test_patch_file_editor() -> Result<(), ParseError<'static>> {
+    let sample = "\
+From: someone
+diff --git a/one.txt b/one.txt
+--- a/one.txt
++++ b/one.txt
+@@ -1,1 +1,1 @@
+-one
++ONE
+
+diff --git a/two.txt b/two.txt
+--- a/two.txt
++++ b/two.txt
+@@ -1,1 +1,1 @@
+-two
++TWO
+";
+    let mut editor = PatchFileEditor::new(sample)?;
+    assert_eq!(editor.to_string(), sample);
+    assert_eq!(editor.position("a/two.txt"), Some(1));
+
+    let mut patch = editor.patches()[0].clone();
+    patch.hunks[0].lines[1] = Line::Add("1");
+    editor.replace(0, patch);
+    assert_eq!(editor.to_string(), sample.replace("+ONE", "+1"));
+
+    editor.remove(1);
+    assert_eq!(editor.position("a/two.txt"), None);
+    let expected = "\
+From: someone
+diff --git a/one.txt b/one.txt
+--- a/one.txt
++++ b/one.txt
+@@ -1,1 +1,1 @@
+-one
++1
+
+";
+    assert_eq!(editor.to_string(), expected);
+    Ok(())
+}
+
+#[test]
+fn test_context_format() -> Result<(), ParseError<'static>> {
+    // Written by `diff -u` and `diff -c` for the same files
+    let unified = "\
+--- old
++++ new
+@@ -1,11 +1,11 @@
+ a
+-b
++B
+ c
+ d
++new
+ e
+ f
+ g
+ h
+ i
+-j
+-k
++k
+\\ No newline at end of file
+";
+    let context = "\
+*** old
+--- new
+***************
+*** 1,11 ****
+  a
+! b
+  c
+  d
+  e
+  f
+  g
+  h
+  i
+! j
+! k
+--- 1,11 ----
+  a
+! B
+  c
+  d
++ new
+  e
+  f
+  g
+  h
+  i
+! k
+\\ No newline at end of file
+";
+    let patch = Patch::from_single(unified)?;
+    assert_eq!(patch.to_context_format(), context.trim_end_matches('\n'));
+
+    let patch = Patch::from_single("--- e0\n+++ e1\n@@ -0,0 +1 @@\n+x\n")?;
+    assert_eq!(
+        patch.to_context_format(),
+        "*** e0\n--- e1\n***************\n*** 0 ****\n--- 1 ----\n+ x"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parse_normal_diff() -> Result<(), ParseError<'static>> {
+    // Written by `diff` for the same files as `test_context_format`
+    let sample = "\
+2c2
+< b
+---
+> B
+4a5
+> new
+10,11c11
+< j
+< k
+---
+> k
+\\ No newline at end of file
+";
+    let patch = Patch::from_normal(sample, "old", "new")?;
+    let headers: Vec<String> = patch
+        .hunks
+        .iter()
+        .map(|hunk| format!("-{} +{}", hunk.old_range, hunk.new_range))
+        .collect();
+    assert_eq!(headers, ["-2,1 +2,1", "-4,0 +5,1", "-10,2 +11,1"]);
+    assert_eq!(
+        patch.hunks[2].lines,
+        [Line::Remove("j"), Line::Remove("k"), Line::Add("k")]
+    );
+    assert!(patch.old_end_newline);
+    assert!(!patch.new_end_newline);
+
+    let patch = Patch::from_normal(
+        "3,11d2\n< c\n< d\n< e\n< f\n< g\n< h\n< i\n< j\n< \n",
+        "a",
+        "b",
+    )?;
+    assert_eq!(patch.hunks[0].new_range, Range { start: 2, count: 0 });
+    assert_eq!(patch.hunks[0].lines[8], Line::Remove(""));
+
+    // An inverted range is an error, not an overflow
+    let err = Patch::from_normal("5,3d4\n< a\n", "a", "b").unwrap_err();
+    assert_eq!(err.line, 1);
+    Ok(())
+}
+
+#[test]
+fn test_parse_svn() -> Result<(), ParseError<'static>> {
+    let sample = include_str!("samples/svn_properties.diff");
+    let patches = Patch::from_multiple(sample)?;
+    assert_eq!(patches.len(), 2);
+    let svn = patches[0].svn.as_ref().unwrap();
+    assert_eq!(svn.path, "trunk/hello.c");
+    assert!(svn.property_changes.is_empty());
+    assert_eq!(patches[0].preamble, None);
+
+    // Only properties changed
+    assert!(patches[1].hunks.is_empty());
+    let changes = &patches[1].svn.as_ref().unwrap().property_changes;
+    assert_eq!(changes[0].name, "svn:eol-style");
+    assert_eq!(changes[0].kind, PropertyChangeKind::Added);
+    assert_eq!(changes[0].hunk.lines, [Line::Add("native")]);
+    assert!(!changes[0].new_end_newline);
+    assert_eq!(changes[1].kind, PropertyChangeKind::Modified);
+    assert_eq!(changes[1].hunk.new_text(), "*.o\nbuild\n*.tmp\n");
+
+    let properties = &sample[sample.find("\n\nProperty changes").unwrap()..];
+    let formatted = patches[1].to_string();
+    assert!(formatted.starts_with("Index: trunk/README\n===="));
+    assert_eq!(
+        &formatted[formatted.find("\n\nProperty changes").unwrap()..],
+        properties
+            .trim_end()
+            .replace("## -0,0 +1 ##", "## -0,0 +1,1 ##")
+    );
+    assert_eq!(Patch::from_single(&formatted).unwrap(), patches[1]);
+
+    // Subversion before 1.7 leaves out the file headers of patches without hunks
+    let old = "\
+Index: README
+===================================================================
+
+Property changes on: README
+___________________________________________________________________
+Added: svn:eol-style
+## -0,0 +1 ##
++native
+";
+    let patch = Patch::from_single(old)?;
+    assert_eq!(patch.new.path, "README");
+    assert_eq!(patch.svn.unwrap().property_changes.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_parse_cvs() -> Result<(), ParseError<'static>> {
+    let sample = include_str!("samples/cvs.diff");
+    let patches = Patch::from_multiple(sample)?;
+    assert_eq!(patches.len(), 2);
+    let cvs = patches[0].cvs.as_ref().unwrap();
+    assert_eq!(cvs.path, "src/main.c");
+    assert_eq!(cvs.rcs_file, "/cvsroot/hello/src/main.c,v");
+    assert_eq!(cvs.revisions, ["1.2", "1.3"]);
+    assert_eq!(cvs.diff_command, Some("diff -u -r1.2 -r1.3"));
+    assert_eq!(patches[0].svn, None);
+    assert_eq!(patches[0].new.path, "src/main.c");
+    assert_eq!(patches[1].cvs.as_ref().unwrap().revisions, ["1.1"]);
+
+    let formatted = patches[1].to_string();
+    let section = &sample[sample.find("Index: README").unwrap()..];
+    let header = &section[..section.find("--- README").unwrap()];
+    assert!(formatted.starts_with(header));
+    assert_eq!(Patch::from_single(&formatted).unwrap(), patches[1]);
+    Ok(())
+}
+
+#[test]
+fn test_parse_perforce() -> Result<(), ParseError<'static>> {
+    let sample = include_str!("samples/p4.diff");
+    let patches = Patch::from_multiple(sample)?;
+    assert_eq!(patches.len(), 2);
+    let perforce = patches[0].perforce.as_ref().unwrap();
+    assert_eq!(perforce.old_path, "//depot/main/src/hello.c");
+    assert_eq!(perforce.old_revision, Some("3"));
+    assert_eq!(
+        perforce.new_path.as_deref(),
+        Some("/home/me/ws/main/src/hello.c")
+    );
+    assert_eq!(perforce.new_revision, None);
+    assert_eq!(patches[0].old.path, "//depot/main/src/hello.c");
+    assert_eq!(patches[0].new.path, "/home/me/ws/main/src/hello.c");
+    assert_eq!(
+        patches[1].hunks[0].lines,
+        [Line::Context("hello"), Line::Add("world")]
+    );
+    assert_eq!(
+        format!("{}\n", patches[1]),
+        sample[sample.find("==== //depot/main/README").unwrap()..]
+    );
+
+    // `p4 describe` writes only the depot path, with the file type, and an empty line
+    let sample = "\
+==== //depot/main/README#2 (text) ====
+
+@@ -1,1 +1,2 @@
+ hello
++world
+";
+    let patch = Patch::from_single(sample)?;
+    let perforce = patch.perforce.as_ref().unwrap();
+    assert_eq!(perforce.old_revision, Some("2"));
+    assert_eq!(perforce.old_file_type, Some("text"));
+    assert_eq!(perforce.new_path, None);
+    assert_eq!(patch.new.path, "//depot/main/README");
+    assert_eq!(format!("{}\n", patch), sample);
+    Ok(())
+}
+
+#[test]
+fn test_dep3_headers() -> Result<(), ParseError<'static>> {
+    let patch = Patch::from_single(include_str!("samples/quilt/fix-greeting.patch"))?;
+    let headers = patch.dep3_headers().unwrap();
+    assert_eq!(headers.description(), Some("Fix the greeting"));
+    assert_eq!(headers.author(), Some("phil <uniphil@gmail.com>"));
+    assert_eq!(headers.forwarded(), Some("not-needed"));
+    assert_eq!(headers.origin(), None);
+
+    // Free-form text between the fields is skipped
+    let sample = "\
+Subject: Build with the system's zlib
+Bug: https://github.com/madler/zlib/issues/1
+
+This keeps the vendored copy from being used: https://example.com
+
+Bug-Ubuntu: https://launchpad.net/bugs/2
+--- a/Makefile
++++ b/Makefile
+@@ -1,1 +1,1 @@
+-ZLIB = vendor/zlib
++ZLIB = /usr/lib
+";
+    let headers = Patch::from_single(sample)?.dep3_headers().unwrap();
+    assert_eq!(headers.fields.len(), 3);
+    assert_eq!(headers.description(), Some("Build with the system's zlib"));
+    assert_eq!(
+        headers.bugs(),
+        [
+            ("", "https://github.com/madler/zlib/issues/1"),
+            ("Ubuntu", "https://launchpad.net/bugs/2"),
+        ]
+    );
+
+    let patch = Patch::from_single("--- a\n+++ b\n@@ -1,1 +1,1 @@\n-a\n+b\n")?;
+    assert_eq!(patch.dep3_headers(), None);
+    Ok(())
+}
+
+#[test]
+fn test_parse_recursive_diff() -> Result<(), ParseError<'static>> {
+    // Written by `diff -ruN`, and `diff -r` without `-N` for the `Only in` lines
+    let sample = "\
+diff -ruN old/notes.txt new/notes.txt
+--- old/notes.txt	2002-02-21 23:30:39.942229878 -0800
++++ new/notes.txt	2002-02-21 23:30:50.442260588 -0800
+@@ -1,1 +1,1 @@
+-hello
++goodbye
+Only in old: removed.txt
+Files old/data.bin and new/data.bin differ
+Only in new/docs: guide.txt
+";
+    assert!(Patch::from_multiple(sample).is_err());
+    let entries = Patch::from_recursive_diff(sample)?;
+    assert_eq!(entries.len(), 4);
+    match &entries[0] {
+        DiffEntry::Patch(patch) => assert_eq!(patch.hunks.len(), 1),
+        entry => panic!("expected a patch, got {:?}", entry),
+    }
+    assert_eq!(
+        entries[1..],
+        [
+            DiffEntry::OnlyIn {
+                dir: "old",
+                name: "removed.txt",
+            },
+            DiffEntry::BinaryDiffer {
+                old: "old/data.bin".into(),
+                new: "new/data.bin".into(),
+                escaped: false,
+            },
+            DiffEntry::OnlyIn {
+                dir: "new/docs",
+                name: "guide.txt",
+            },
+        ]
+    );
+    let formatted: String = entries[1..]
+        .iter()
+        .map(|entry| format!("{}\n", entry))
+        .collect();
+    assert_eq!(formatted, sample[sample.find("Only in old").unwrap()..]);
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use patch::{Line, ParseError, RangeDiff, RangeDiffCommit, RangeDiffStatus};
+
+use pretty_assertions::assert_eq;
+
+#[test]
+fn test_parse_range_diff() -> Result<(), ParseError<'static>> {
+    let sample = "\
+1:  6d2af67 ! 1:  56d16ca Change two
+    @@ f.txt
+     @@
+      one
+     -two
+    -+2
+    ++TWO
+      three
+2:  033c913 = 2:  2873b86 Add g
+3:  fc81707 < -:  ------- Add h
+-:  ------- > 3:  67e48e7 Add i
+";
+    let range_diff = RangeDiff::parse(sample)?;
+    let statuses: Vec<_> = range_diff.entries.iter().map(|e| e.status).collect();
+    assert_eq!(
+        statuses,
+        [
+            RangeDiffStatus::Changed,
+            RangeDiffStatus::Unchanged,
+            RangeDiffStatus::Removed,
+            RangeDiffStatus::Added,
+        ]
+    );
+
+    let changed = &range_diff.entries[0];
+    assert_eq!(
+        changed.new,
+        Some(RangeDiffCommit {
+            index: 1,
+            hash: "56d16ca"
+        })
+    );
+    assert_eq!(changed.sections[0].header, " f.txt");
+    assert_eq!(
+        changed.sections[0].lines,
+        [
+            Line::Context("@@"),
+            Line::Context(" one"),
+            Line::Context("-two"),
+            Line::Remove("+2"),
+            Line::Add("+TWO"),
+            Line::Context(" three"),
+        ]
+    );
+    assert_eq!(range_diff.entries[2].new, None);
+    assert_eq!(range_diff.entries[3].subject, "Add i");
+
+    assert_eq!(format!("{}\n", range_diff), sample);
+    assert!(RangeDiff::parse("")?.entries.is_empty());
+    Ok(())
+}
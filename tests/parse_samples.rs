@@ -1,9 +1,15 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use pretty_assertions::assert_eq;
 
-use patch::Patch;
+use patch::{ParserOptions, Patch, PatchEvent, PatchEvents};
+
+// Samples whose hunks have more or fewer lines than their ranges say
+const MALFORMED: &[&str] = &["sample6.diff", "sample7.diff"];
+
+// Samples whose hunk header counts are off, which only forgiving parsing accepts
+const MISCOUNTED: &[&str] = &["sample1.diff", "sample2.diff"];
 
 #[test]
 fn parse_samples() {
@@ -15,9 +21,45 @@ fn parse_samples() {
         }
 
         let data = fs::read_to_string(dbg!(&path)).unwrap();
+        if MISCOUNTED.contains(&path.file_name().unwrap().to_str().unwrap()) {
+            assert!(
+                Patch::from_multiple(&data).is_err(),
+                "{:?} should fail",
+                path
+            );
+            let patches = Patch::from_multiple_with_options(&data, &ParserOptions::forgiving())
+                .unwrap_or_else(|err| panic!("failed to parse {:?}, error: {}", path, err));
+            let files = data.lines().filter(|line| line.starts_with("+++ ")).count();
+            assert_eq!(patches.len(), files, "{:?}", path);
+            continue;
+        }
+        if MALFORMED.contains(&path.file_name().unwrap().to_str().unwrap()) {
+            assert!(
+                Patch::scan_headers(&data).is_err(),
+                "{:?} should fail",
+                path
+            );
+            assert!(
+                Patch::from_multiple(&data).is_err(),
+                "{:?} should fail",
+                path
+            );
+            assert!(
+                PatchEvents::new(&data).any(|event| event.is_err()),
+                "{:?} should fail",
+                path
+            );
+            continue;
+        }
         let patches = Patch::from_multiple(&data)
             .unwrap_or_else(|err| panic!("failed to parse {:?}, error: {}", path, err));
 
+        assert_events_agree(&path, &data, &patches);
+
+        // Parsing one patch at a time finds the same patches
+        let iterated: Result<Vec<_>, _> = Patch::iter_multiple(&data).collect();
+        assert_eq!(iterated.unwrap(), patches);
+
         // Make sure that the patch file we produce parses to the same information as the original
         // patch file.
         let patch_file: String = patches.iter().map(|patch| format!("{}\n", patch)).collect();
@@ -29,5 +71,79 @@ fn parse_samples() {
             )
         });
         assert_eq!(patches, patches2);
+
+        // Scanning only the headers finds the same files and hunks
+        let headers = Patch::scan_headers(&data).unwrap();
+        assert_eq!(headers.len(), patches.len());
+        for (headers, patch) in headers.iter().zip(&patches) {
+            assert_eq!((&headers.old, &headers.new), (&patch.old, &patch.new));
+            let ranges: Vec<_> = headers.hunks.iter().map(|h| &h.new_range).collect();
+            let expected: Vec<_> = patch.hunks.iter().map(|h| &h.new_range).collect();
+            assert_eq!(ranges, expected);
+        }
+
+        #[cfg(feature = "fuzzing")]
+        patch::fuzzing::assert_lenient_agrees(&data);
     }
 }
+
+// Checks that the events have the same files, hunks and lines as the patches
+fn assert_events_agree(path: &Path, data: &str, patches: &[Patch<'_>]) {
+    let (mut files, mut ends) = (Vec::new(), 0);
+    for event in PatchEvents::new(data) {
+        match event.unwrap_or_else(|err| panic!("{:?} fails as events, error: {}", path, err)) {
+            PatchEvent::FileHeader(header) => files.push((header.new, Vec::new())),
+            PatchEvent::HunkHeader(_) => files.last_mut().unwrap().1.push(Vec::new()),
+            PatchEvent::Line(line) => {
+                let (_, hunks) = files.last_mut().unwrap();
+                hunks.last_mut().unwrap().push(line);
+            }
+            PatchEvent::EndOfFile => ends += 1,
+        }
+    }
+    let expected: Vec<_> = patches
+        .iter()
+        .map(|patch| {
+            let lines = patch.hunks.iter().map(|hunk| hunk.lines.clone()).collect();
+            (patch.new.clone(), lines)
+        })
+        .collect();
+    assert_eq!(files, expected, "{:?}", path);
+    assert_eq!(ends, patches.len(), "{:?}", path);
+}
+
+#[test]
+fn events_agree_on_inline_input() {
+    let inputs = [
+        // The indicator only follows the last line of a side
+        "--- a\n+++ b\n@@ -1,2 +1,2 @@\n-x\n\\ No newline at end of file\n y\n+z\n",
+        "--- a\n+++ b\n@@ -1,2 +1,2 @@\n y\n-x\n\\ No newline at end of file\n+z\n",
+    ];
+    for data in inputs {
+        let path = Path::new(data);
+        match Patch::from_multiple(data) {
+            Ok(patches) => assert_events_agree(path, data, &patches),
+            Err(_) => assert!(
+                PatchEvents::new(data).any(|event| event.is_err()),
+                "{:?} should fail",
+                path
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "fuzzing")]
+#[test]
+fn lenient_agrees_on_lines_like_headers() {
+    // Lines like headers inside the ranges of a hunk, which only the counts tell apart
+    let sample = "\
+--- main.c
++++ main.c
+@@ -1,3 +1,3 @@
+ int main() {
+--- a;
++++ a;
+ }
+";
+    patch::fuzzing::assert_lenient_agrees(sample);
+}
@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use patch::{Line, ParseError, Series, SeriesEntry};
+
+use pretty_assertions::assert_eq;
+
+#[test]
+fn parse_quilt_series() {
+    let dir = PathBuf::from(file!())
+        .parent()
+        .unwrap()
+        .join("samples/quilt");
+    let text = std::fs::read_to_string(dir.join("series")).unwrap();
+    let series = Series::parse(&text).unwrap();
+    assert_eq!(
+        series.entries,
+        [
+            SeriesEntry {
+                name: "fix-greeting.patch",
+                strip: 1,
+                reverse: false,
+            },
+            SeriesEntry {
+                name: "local-paths.diff",
+                strip: 0,
+                reverse: false,
+            },
+        ]
+    );
+
+    let files = series.read_patch_files(&dir).unwrap();
+    let stack = series.parse_patches(&files).unwrap();
+    assert_eq!(stack.patches.len(), 2);
+    let greeting = &stack.patches[0].files[0];
+    assert!(greeting
+        .preamble
+        .unwrap()
+        .starts_with("Description: Fix the greeting\n"));
+    assert_eq!(
+        greeting.hunks[0].lines,
+        [Line::Remove("helo"), Line::Add("hello")]
+    );
+    assert_eq!(stack.patches[1].entry.strip, 0);
+    assert_eq!(stack.patches[1].files[0].new.path, "config.mk");
+}
+
+#[test]
+fn reject_unknown_series_options() {
+    let err: ParseError<'_> = Series::parse("one.patch\ntwo.patch --fuzz=3\n").unwrap_err();
+    assert_eq!(err.line, 2);
+    assert!(Series::parse("one.patch -px\n").is_err());
+    // A `#` inside a name doesn't start a comment
+    let series = Series::parse("issue#12.patch\n").unwrap();
+    assert_eq!(series.entries[0].name, "issue#12.patch");
+}
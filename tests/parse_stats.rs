@@ -0,0 +1,80 @@
+use patch::{FileStat, ParseError, Patch};
+
+use pretty_assertions::assert_eq;
+
+fn stat(path: &str, additions: u64, deletions: u64, binary: bool) -> FileStat<'_> {
+    FileStat {
+        path: path.into(),
+        additions,
+        deletions,
+        binary,
+    }
+}
+
+#[test]
+fn test_parse_numstat() -> Result<(), ParseError<'static>> {
+    let sample = "\
+200\t0\tbig.txt
+-\t-\tbin.dat
+2\t0\t\"My Work/x.txt\"
+";
+    assert_eq!(
+        FileStat::parse_numstat(sample)?,
+        [
+            stat("big.txt", 200, 0, false),
+            stat("bin.dat", 0, 0, true),
+            stat("My Work/x.txt", 2, 0, false),
+        ]
+    );
+    assert!(FileStat::parse_numstat("")?.is_empty());
+    assert!(FileStat::parse_numstat("1 2 x\n").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_parse_stat() -> Result<(), ParseError<'static>> {
+    let sample = "
+\x20big.txt | 200 +++++++++++++++++++++++++++++++++++++++++++++++++++-------------
+\x20bin.dat | Bin 0 -> 2 bytes
+\x20x.txt   |   2 +
+\x20mode.sh |   0
+\x203 files changed, 202 insertions(+)
+";
+    assert_eq!(
+        FileStat::parse_stat(&sample[1..])?,
+        [
+            stat("big.txt", 159, 41, false),
+            stat("bin.dat", 0, 0, true),
+            stat("x.txt", 2, 0, false),
+            stat("mode.sh", 0, 0, false),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_patch_stat() -> Result<(), ParseError<'static>> {
+    let sample = "\
+--- a/hello.txt
++++ b/hello.txt
+@@ -1,2 +1,3 @@
+ hello
+-world
++there
++everyone
+";
+    let patch = Patch::from_single(sample)?;
+    assert_eq!(patch.stat(), stat("b/hello.txt", 2, 1, false));
+    Ok(())
+}
+
+#[test]
+fn stat_counts_too_large_are_errors() {
+    let sample = " a | 18446744073709551615 ++--\n 1 file changed\n";
+    assert!(FileStat::parse_stat(sample).is_err());
+    let sample = " a | 18446744073709551615 ----\n 1 file changed\n";
+    assert_eq!(
+        FileStat::parse_stat(sample).unwrap(),
+        [stat("a", 0, u64::MAX, false)]
+    );
+}
@@ -0,0 +1,66 @@
+use patch::{ParseError, WordDiffLine, WordDiffPatch, WordDiffSpan};
+
+use pretty_assertions::assert_eq;
+
+#[test]
+fn test_parse_word_diff() -> Result<(), ParseError<'static>> {
+    let sample = "\
+diff --git a/f.txt b/f.txt
+index b02e8a7..ac74c6c 100644
+--- a/f.txt
++++ b/f.txt
+@@ -1,6 +1,7 @@
+the [-quick-]{+slow+} brown [-fox-]{+dog+}
+jumps over
+{+new line+}
+a
+[-b-]c {+X Y+}
+d
+{+e+}
+diff --git a/g.txt b/h.txt
+similarity index 100%
+rename from g.txt
+rename to h.txt
+";
+    let patches = WordDiffPatch::from_multiple(sample)?;
+    assert_eq!(patches.len(), 2);
+
+    let hunk = &patches[0].hunks[0];
+    assert_eq!(hunk.lines.len(), 7);
+    use WordDiffSpan::*;
+    assert_eq!(
+        hunk.lines[0].spans,
+        [
+            Unchanged("the "),
+            Removed("quick"),
+            Added("slow"),
+            Unchanged(" brown "),
+            Removed("fox"),
+            Added("dog"),
+        ]
+    );
+    assert_eq!(hunk.lines[2].spans, [Added("new line")]);
+    assert_eq!(
+        hunk.lines[4].spans,
+        [Removed("b"), Unchanged("c "), Added("X Y")]
+    );
+
+    assert!(patches[1].hunks.is_empty());
+    assert_eq!(patches[1].new.path, "b/h.txt");
+
+    let printed: Vec<String> = patches.iter().map(ToString::to_string).collect();
+    assert_eq!(printed.join("\n") + "\n", sample);
+
+    // Text after the last patch is an error, not a panic
+    let trailing = format!("{}--- i.txt\n", sample);
+    let err = WordDiffPatch::from_multiple(&trailing).unwrap_err();
+    assert_eq!(err.line, 13);
+    Ok(())
+}
+
+#[test]
+fn test_word_diff_line_unclosed_marker() {
+    let line = WordDiffLine::parse("a[-b");
+    assert_eq!(line.spans, [WordDiffSpan::Unchanged("a[-b")]);
+    assert!(WordDiffLine::parse("").spans.is_empty());
+}
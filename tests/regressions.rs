@@ -1,4 +1,7 @@
-use patch::{File, FileMetadata, Hunk, Line, ParseError, Patch, Range};
+use patch::{
+    verify_roundtrip, BinaryHunkKind, BinaryPatch, File, FileMetadata, FileOperation, Hunk, Line,
+    LineEnding, ParseError, ParserOptions, Patch, Range,
+};
 
 use pretty_assertions::assert_eq;
 
@@ -10,7 +13,7 @@ fn hunk_header_context_is_not_a_line_15() -> Result<(), ParseError<'static>> {
 @@ -0,0 +0,0 @@ spoopadoop
  x
 ";
-    let patch = Patch::from_single(sample)?;
+    let patch = Patch::from_single_with_options(sample, &ParserOptions::forgiving())?;
     assert_eq!(patch.hunks[0].lines, [Line::Context("x")]);
     Ok(())
 }
@@ -23,25 +26,36 @@ fn crlf_breaks_stuff_17() -> Result<(), ParseError<'static>> {
 @@ -0,0 +0,0 @@\r
  x\r
 ";
-    let patch = Patch::from_single(sample)?;
+    let patch = Patch::from_single_with_options(sample, &ParserOptions::forgiving())?;
     assert_eq!(
         patch,
         Patch {
             old: File {
                 path: "old.txt".into(),
+                escaped: false,
                 meta: None
             },
             new: File {
                 path: "new.txt".into(),
+                escaped: false,
                 meta: None
             },
+            git: None,
+            svn: None,
+            cvs: None,
+            perforce: None,
             hunks: vec![Hunk {
                 old_range: Range { start: 0, count: 0 },
                 new_range: Range { start: 0, count: 0 },
                 range_hint: "",
                 lines: vec![Line::Context("x")],
             }],
-            end_newline: true,
+            binary: None,
+            old_end_newline: true,
+            new_end_newline: true,
+            line_ending: LineEnding::CrLf,
+            byte_order_mark: false,
+            preamble: None,
         }
     );
     Ok(())
@@ -55,11 +69,12 @@ fn unquoted_filenames_with_spaces_11() -> Result<(), ParseError<'static>> {
 @@ -0,0 +0,0 @@
  x
 ";
-    let patch = Patch::from_single(sample)?;
+    let patch = Patch::from_single_with_options(sample, &ParserOptions::forgiving())?;
     assert_eq!(
         patch.old,
         File {
             path: "unquoted no space".into(),
+            escaped: false,
             meta: None,
         }
     );
@@ -67,8 +82,205 @@ fn unquoted_filenames_with_spaces_11() -> Result<(), ParseError<'static>> {
         patch.new,
         File {
             path: "unquoted no space".into(),
+            escaped: false,
             meta: Some(FileMetadata::Other("with metadata".into())),
         }
     );
     Ok(())
 }
+
+#[test]
+fn rename_only_diffs_have_no_hunks() -> Result<(), ParseError<'static>> {
+    let sample = "\
+diff --git a/old_name.txt b/new_name.txt
+similarity index 100%
+rename from old_name.txt
+rename to new_name.txt
+";
+    let patches = Patch::from_multiple(sample)?;
+    assert_eq!(patches.len(), 1);
+    let patch = &patches[0];
+    assert_eq!(patch.old.path, "a/old_name.txt");
+    assert_eq!(patch.new.path, "b/new_name.txt");
+    assert!(patch.hunks.is_empty());
+    assert_eq!(
+        patch.git.as_ref().unwrap().operation,
+        Some(FileOperation::Renamed {
+            from: "old_name.txt".into(),
+            to: "new_name.txt".into(),
+            escaped: false,
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn mode_only_diffs_have_no_hunks() -> Result<(), ParseError<'static>> {
+    let sample = "\
+diff --git a/build.sh b/build.sh
+old mode 100644
+new mode 100755
+diff --git a/run.sh b/run.sh
+index 8d1c8b6..e5a71c4 100644
+--- a/run.sh
++++ b/run.sh
+@@ -1,1 +1,1 @@
+-echo hello
++echo goodbye
+";
+    let patches = Patch::from_multiple(sample)?;
+    assert_eq!(patches.len(), 2);
+    assert!(patches[0].hunks.is_empty());
+    assert_eq!(
+        patches[0].git.as_ref().unwrap().mode_change(),
+        Some((0o100644, 0o100755))
+    );
+    assert_eq!(patches[1].git.as_ref().unwrap().mode_change(), None);
+    Ok(())
+}
+
+#[test]
+fn similarity_index_is_kept() -> Result<(), ParseError<'static>> {
+    let sample = "\
+diff --git a/a.txt b/b.txt
+similarity index 87%
+rename from a.txt
+rename to b.txt
+diff --git a/c.txt b/c.txt
+dissimilarity index 100%
+index 1f38447..8946660 100644
+--- a/c.txt
++++ b/c.txt
+@@ -1,1 +1,1 @@
+-old
++new
+";
+    let patches = Patch::from_multiple(sample)?;
+    assert_eq!(patches[0].git.as_ref().unwrap().similarity, Some(87));
+    assert_eq!(patches[1].git.as_ref().unwrap().dissimilarity, Some(100));
+
+    let output: String = patches.iter().map(|p| format!("{}\n", p)).collect();
+    assert_eq!(output, sample);
+    Ok(())
+}
+
+#[test]
+fn git_binary_patches_parse() -> Result<(), ParseError<'static>> {
+    let sample = include_str!("samples/git_binary.diff");
+    let patches = Patch::from_multiple(sample)?;
+    assert_eq!(patches.len(), 3);
+    assert!(patches.iter().all(|patch| patch.hunks.is_empty()));
+
+    match &patches[1].binary {
+        Some(BinaryPatch::Git {
+            forward,
+            reverse: Some(reverse),
+        }) => {
+            assert_eq!(forward.kind, BinaryHunkKind::Literal);
+            assert_eq!(forward.size, 200);
+            assert_eq!(reverse.size, 6);
+            // zlib header
+            assert_eq!(&reverse.data[..2], &[0x78, 0x01]);
+        }
+        binary => panic!("unexpected binary patch: {:?}", binary),
+    }
+
+    let output: String = patches.iter().map(|p| format!("{}\n", p)).collect();
+    assert_eq!(output, sample);
+    Ok(())
+}
+
+#[test]
+fn binary_files_differ_entries() -> Result<(), ParseError<'static>> {
+    let sample = include_str!("samples/binary_differ.diff");
+    let patches = Patch::from_multiple(sample)?;
+    assert_eq!(patches.len(), 4);
+
+    assert_eq!(patches[0].binary, Some(BinaryPatch::Changed));
+    assert_eq!(patches[0].old.path, "a/image.png");
+    assert_eq!(patches[0].new.path, "b/image.png");
+
+    assert_eq!(patches[1].binary, Some(BinaryPatch::Changed));
+    assert_eq!(patches[1].old.path, "/dev/null");
+    assert_eq!(patches[1].new.path, "b/logo and icon.png");
+
+    assert_eq!(patches[2].git, None);
+    assert_eq!(patches[2].binary, Some(BinaryPatch::Changed));
+    assert_eq!(patches[2].new.path, "new/data.bin");
+
+    assert_eq!(patches[3].binary, None);
+    assert_eq!(patches[3].hunks.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn git_quote_path_octal_escapes() -> Result<(), ParseError<'static>> {
+    let sample = r#"diff --git "a/\346\226\260\346\226\207\344\273\266.txt" "b/\346\226\260\346\226\207\344\273\266.txt"
+index 7898192..6178079 100644
+--- "a/\346\226\260\346\226\207\344\273\266.txt"
++++ "b/\346\226\260\346\226\207\344\273\266.txt"
+@@ -1 +1 @@
+-a
++b
+"#;
+    let patch = Patch::from_single(sample)?;
+    assert_eq!(patch.old.path, "a/新文件.txt");
+    assert_eq!(patch.new.path, "b/新文件.txt");
+    assert_eq!(patch.git.as_ref().unwrap().new_path, "b/新文件.txt");
+    // Paths are escaped the same way git does it
+    let headers = &sample[..sample.find("@@").unwrap()];
+    assert_eq!(&format!("{}", patch)[..headers.len()], headers);
+    Ok(())
+}
+
+#[test]
+fn bare_utf8_path_round_trips() -> Result<(), ParseError<'static>> {
+    let sample = "--- café.txt\n+++ café.txt\n@@ -1,1 +1,1 @@\n-a\n+b\n";
+    let report = verify_roundtrip(sample)?;
+    assert_eq!(report.first_differing_byte, None);
+    assert!(report.is_lossless());
+
+    // Quoted because of the space, but the UTF-8 is written as it was read
+    let sample =
+        "--- \"a/café au lait.txt\"\n+++ \"b/café au lait.txt\"\n@@ -1,1 +1,1 @@\n-a\n+b\n";
+    assert_eq!(format!("{}\n", Patch::from_single(sample)?), sample);
+
+    // Also when other characters in the path are escaped
+    let sample = "--- \"a/café \\\"au lait\\\".txt\"\n+++ \"b/café \\\"au lait\\\".txt\"\n@@ -1,1 +1,1 @@\n-a\n+b\n";
+    let patch = Patch::from_single(sample)?;
+    assert!(!patch.old.escaped);
+    assert_eq!(format!("{}\n", patch), sample);
+
+    // Escaped non-ASCII characters are escaped again
+    let sample =
+        "--- \"a/caf\\303\\251.txt\"\n+++ \"b/caf\\303\\251.txt\"\n@@ -1,1 +1,1 @@\n-a\n+b\n";
+    let patch = Patch::from_single(sample)?;
+    assert!(patch.old.escaped);
+    assert_eq!(patch.old.path, "a/café.txt");
+    assert_eq!(format!("{}\n", patch), sample);
+    Ok(())
+}
+
+#[test]
+fn byte_order_mark_before_header() -> Result<(), ParseError<'static>> {
+    let sample = "\u{feff}--- old.txt\r\n+++ new.txt\r\n@@ -1,1 +1,1 @@\r\n-a\r\n+b\r\n";
+    let patch = Patch::from_single(sample)?;
+    assert!(patch.byte_order_mark);
+    assert_eq!(patch.old.path, "old.txt");
+    assert_eq!(format!("{}\r\n", patch), sample);
+
+    let patches = Patch::from_multiple(sample)?;
+    assert!(patches[0].byte_order_mark);
+    Ok(())
+}
+
+#[test]
+fn last_hunk_line_without_newline() -> Result<(), ParseError<'static>> {
+    let sample = "--- old.txt\n+++ new.txt\n@@ -1,1 +1,1 @@\n-a\n+b";
+    let patch = Patch::from_single(sample)?;
+    assert_eq!(patch.hunks[0].lines, [Line::Remove("a"), Line::Add("b")]);
+
+    let hunk = Hunk::from_fragment("\n@@ -1,1 +1,1 @@\n-a\n+b")?;
+    assert_eq!(hunk.lines, [Line::Remove("a"), Line::Add("b")]);
+    Ok(())
+}